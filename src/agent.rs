@@ -1,11 +1,19 @@
-//TODO change borrow references
+//! Module for the agents of the simulated economy, after Doran and
+//! Parberry's "Emergent Economies for Role Playing Games". Each agent
+//! makes commodities from the inputs in their recipes, offers what it
+//! makes and bids for what it needs, and adjusts what it believes each
+//! commodity is worth from how its offers go.
 
-use crate::commodities::Commodity;
+use crate::commodities::{Commodities, CommodityKind};
 use crate::market_house::{Trade, TradeSubmission};
 use rand::Rng;
-use std::cmp::{max, min};
 use std::collections::HashMap;
 
+/// Share of its cash an agent loses each round it makes nothing
+const IDLE_TAX_RATE: f32 = 0.05;
+/// Widest an agent's price beliefs can get from failing to trade
+const MAX_WOBBLE: f32 = 0.2;
+
 pub struct History {
     items: Vec<f32>,
     min: f32,
@@ -21,6 +29,13 @@ impl History {
         }
     }
     pub fn add(&mut self, new_val: f32) {
+        if self.items.is_empty() {
+            self.min = new_val;
+            self.max = new_val;
+        } else {
+            self.min = self.min.min(new_val);
+            self.max = self.max.max(new_val);
+        }
         self.items.push(new_val);
     }
     pub fn get_min(&self) -> f32 {
@@ -32,7 +47,7 @@ impl History {
 }
 
 pub struct CommodityStock {
-    commodity_name: String,
+    commodity: CommodityKind,
     quantity: f32,
     max_quantity: f32,
     mean_cost: f32,
@@ -51,7 +66,7 @@ pub struct CommodityStock {
 
 impl CommodityStock {
     pub fn new(
-        _name: String,
+        _kind: CommodityKind,
         _quantity: f32,
         _max_quantity: f32,
         _mean_price: f32,
@@ -61,7 +76,7 @@ impl CommodityStock {
         price_history.add(_mean_price);
 
         CommodityStock {
-            commodity_name: _name,
+            commodity: _kind,
             quantity: _quantity,
             max_quantity: _max_quantity,
             mean_cost: _mean_price,
@@ -90,36 +105,21 @@ impl CommodityStock {
 
     pub fn buy(&mut self, quant: f32, price: f32) -> f32 {
         let total_cost = self.mean_cost * self.quantity + price * quant;
-        self.mean_cost = total_cost / self.quantity;
-        let mut left_over = quant - self.deficit();
-        self.quantity += quant;
-        if left_over > 0.0 {
-            println!(
-                "Bought too much! Max: {} {} \nleftover:{}",
-                self.quantity, quant, left_over
-            );
-        } else {
-            left_over = 0.0;
+        if self.quantity + quant > 0.0 {
+            self.mean_cost = total_cost / (self.quantity + quant);
         }
+        self.quantity += quant;
         self.update_price_belief(false, price, true);
         quant
     }
     pub fn sell(&mut self, quant: f32, price: f32) {
-        self.quantity = quant;
+        self.quantity = (self.quantity - quant).max(0.0);
         self.update_price_belief(true, price, true);
     }
     pub fn get_price(&mut self) -> f32 {
         self.sane_price_beliefs();
         let mut rng = rand::thread_rng();
-        //let p: f32 = (rng.gen() as f32)*(self.max_price_belief-self.min_price_belief) + self.min_price_belief;
-        let p = rng.gen_range(self.min_price_belief..self.max_price_belief) as f32;
-        if p > 1000.0 {
-            println!(
-                "price beliefs {}, {}",
-                self.min_price_belief, self.max_price_belief
-            )
-        }
-        p
+        rng.gen_range(self.min_price_belief..=self.max_price_belief)
     }
     fn sane_price_beliefs(&mut self) {
         //self.min_price_belief = max(self.cost,self.min_price_belief);
@@ -134,7 +134,9 @@ impl CommodityStock {
         if self.min_price_belief > self.max_price_belief {
             println!(
                 "{} ERROR {} > {}",
-                self.commodity_name, self.min_price_belief, self.max_price_belief
+                self.commodity.name(),
+                self.min_price_belief,
+                self.max_price_belief
             );
         }
         self.price_history.add(price);
@@ -162,18 +164,18 @@ impl CommodityStock {
         } else {
             self.min_price_belief -= delta_mean / 4.0;
             self.max_price_belief -= delta_mean / 4.0;
+            // Running out of stock to use, or of room to keep it, makes
+            // the agent less sure of its prices
             if (buy && self.quantity < self.max_quantity * self.low_inventory)
                 || (is_sell && self.quantity > self.max_quantity * self.low_inventory)
             {
-                println!("TODO");
-            } else {
-                println!("TODO");
+                self.wobble = (self.wobble * 2.0).min(MAX_WOBBLE);
             }
             self.min_price_belief -= self.wobble * mean;
             self.max_price_belief += self.wobble * mean
         }
 
-        if self.min_price_belief < self.max_price_belief {
+        if self.min_price_belief > self.max_price_belief {
             self.min_price_belief = self.max_price_belief / 2.0;
         }
 
@@ -187,16 +189,23 @@ impl CommodityStock {
     }
 }
 
+/// Where `value` falls between `low` and `high`, from 0 to 1
+fn position_in_range(value: f32, low: f32, high: f32) -> f32 {
+    if high <= low {
+        return 0.5;
+    }
+    ((value - low) / (high - low)).clamp(0.0, 1.0)
+}
+
 pub struct EconAgent {
     debug: i32,
     cash: f32,
     prev_cash: f32,
     max_stock: f32,
     profits: Vec<f32>, //TODO ESList?
-    stock_pile: HashMap<String, CommodityStock>,
-    stock_pile_cost: HashMap<String, f32>,
-    buildables: Vec<String>,
-    com: HashMap<String, Commodity>,
+    stock_pile: HashMap<CommodityKind, CommodityStock>,
+    stock_pile_cost: HashMap<CommodityKind, f32>,
+    buildables: Vec<CommodityKind>,
     bankruptcy_threshold: f32,
     history_count: i32,
 }
@@ -212,7 +221,6 @@ impl EconAgent {
             stock_pile: HashMap::new(),
             stock_pile_cost: HashMap::new(),
             buildables: Vec::new(),
-            com: HashMap::new(),
             bankruptcy_threshold: -200.0,
             history_count: 10,
         }
@@ -224,41 +232,63 @@ impl EconAgent {
 
     pub fn add_to_stock_pile(
         &mut self,
-        name: String,
+        kind: CommodityKind,
         num: f32,
         max: f32,
         price: f32,
         production: f32,
     ) {
-        if !self.stock_pile.contains_key(&name) {
-            self.stock_pile.insert(
-                name.clone(),
-                CommodityStock::new(name.clone(), num, max, price, production),
-            );
-        }
-        self.stock_pile_cost.insert(
-            name.clone(),
-            self.com.get_mut(&name).unwrap().get_price() * num,
-        );
+        self.stock_pile
+            .entry(kind)
+            .or_insert_with(|| CommodityStock::new(kind, num, max, price, production));
+        self.stock_pile_cost.insert(kind, price * num);
     }
 
-    //TODO
-    pub fn init_agent(&mut self, init_cash: f32, b: &Vec<String>, init_num: f32, _max_stock: f32) {
-        /*
-        if self.com.len() == 0{
-            self.buildables = b;
-            self.cash = init_cash;
-            self.prev_cash = self.cash;
-            self.max_stock = _max_stock;
-
-            for_each!(buildable in buildables{
-                if(!com.contains_key(buildable)){
-                    println!("Commodity not recognized {}",buildable);
-                }
+    /// Set the agent up to make each of `b`, with `init_num` of every
+    /// input their recipes need
+    pub fn init_agent(
+        &mut self,
+        init_cash: f32,
+        b: &Vec<CommodityKind>,
+        init_num: f32,
+        _max_stock: f32,
+        com: &mut Commodities,
+    ) {
+        if !self.stock_pile.is_empty() {
+            return;
+        }
+        self.buildables = b.clone();
+        self.cash = init_cash;
+        self.prev_cash = self.cash;
+        self.max_stock = _max_stock;
 
-            });
+        for buildable in b {
+            let (production, inputs) = match com.get(*buildable) {
+                Some(c) => (
+                    c.get_production(),
+                    c.get_dependency()
+                        .iter()
+                        .map(|(k, _)| *k)
+                        .collect::<Vec<CommodityKind>>(),
+                ),
+                None => {
+                    println!("Commodity not recognized {}", buildable.name());
+                    continue;
+                }
+            };
+            let price = com.get_mut(*buildable).unwrap().get_price();
+            self.add_to_stock_pile(*buildable, 0.0, _max_stock, price, production);
+            for input in inputs {
+                let price = match com.get_mut(input) {
+                    Some(c) => c.get_price(),
+                    None => {
+                        println!("Commodity not recognized {}", input.name());
+                        continue;
+                    }
+                };
+                self.add_to_stock_pile(input, init_num, _max_stock, price, 1.0);
+            }
         }
-        */
     }
 
     pub fn tax_profit(&mut self, tax_rate: f32) -> f32 {
@@ -280,9 +310,40 @@ impl EconAgent {
     pub fn is_bankrupt(&mut self) -> bool {
         self.cash < self.bankruptcy_threshold
     }
+    pub fn get_cash(&self) -> f32 {
+        self.cash
+    }
+    pub fn add_cash(&mut self, amount: f32) {
+        self.cash += amount;
+    }
+    pub fn get_buildables(&self) -> &Vec<CommodityKind> {
+        &self.buildables
+    }
+    /// How many of `commodity` the agent holds
+    pub fn get_quantity(&self, commodity: CommodityKind) -> f32 {
+        self.stock_pile.get(&commodity).map_or(0.0, |s| s.quantity)
+    }
+    /// Use up to `quantity` of `commodity`, returning how many were used
+    pub fn use_up(&mut self, commodity: CommodityKind, quantity: f32) -> f32 {
+        match self.stock_pile.get_mut(&commodity) {
+            Some(s) => {
+                let used = quantity.min(s.quantity);
+                s.increase_quantity(-used);
+                used
+            }
+            None => 0.0,
+        }
+    }
+    /// Add `quantity` of `commodity` to the agent's stock from outside
+    /// the market
+    pub fn add_stock(&mut self, commodity: CommodityKind, quantity: f32) {
+        if let Some(s) = self.stock_pile.get_mut(&commodity) {
+            s.increase_quantity(quantity);
+        }
+    }
     //TODO
     pub fn tick(&self) {}
-    pub fn buy(&mut self, commodity: String, quantity: f32, price: f32) -> f32 {
+    pub fn buy(&mut self, commodity: CommodityKind, quantity: f32, price: f32) -> f32 {
         let bought_quantity = self
             .stock_pile
             .get_mut(&commodity)
@@ -291,150 +352,196 @@ impl EconAgent {
         self.cash -= price * bought_quantity;
         bought_quantity
     }
-    pub fn sell(&mut self, commodity: String, quantity: f32, price: f32) {
+    pub fn sell(&mut self, commodity: CommodityKind, quantity: f32, price: f32) {
         self.stock_pile
             .get_mut(&commodity)
             .unwrap()
-            .sell(-quantity, price);
+            .sell(quantity, price);
         self.cash += price * quantity;
     }
-    pub fn reject_ask(&mut self, commodity: String, price: f32) {
+    pub fn reject_ask(&mut self, commodity: CommodityKind, price: f32) {
         self.stock_pile
             .get_mut(&commodity)
             .unwrap()
             .update_price_belief(true, price, false);
     }
-    pub fn reject_bid(&mut self, commodity: String, price: f32) {
+    pub fn reject_bid(&mut self, commodity: CommodityKind, price: f32) {
         self.stock_pile
             .get_mut(&commodity)
             .unwrap()
             .update_price_belief(false, price, false);
     }
-    fn find_sell_count(&mut self, c: String) -> f32 {
-        let avg_price = self
-            .com
-            .get_mut(&c)
-            .unwrap()
-            .get_avg_price(self.history_count);
-        let lowest_price = self.stock_pile.get_mut(&c).unwrap().price_history.get_min();
-        let highest_price = self.stock_pile.get_mut(&c).unwrap().price_history.get_max();
-        let mut favorability: f32 = 0.5; // TODO LERP 1.0/avg_price.lerp(lowest_price,highest_price);
-        favorability = favorability.clamp(0.0, 1.0);
-        let num_asks = favorability * self.stock_pile.get_mut(&c).unwrap().surplus();
+    /// How favorable the market price of `c` is compared to the prices
+    /// the agent has traded it at, from 0 (cheapest seen) to 1 (dearest)
+    fn favorability(&mut self, c: CommodityKind, com: &mut Commodities) -> f32 {
+        let avg_price = match com.get(c) {
+            Some(commodity) => commodity.get_avg_price(self.history_count),
+            None => return 0.5,
+        };
+        let history = &self.stock_pile[&c].price_history;
+        position_in_range(avg_price, history.get_min(), history.get_max())
+    }
+    fn find_sell_count(&mut self, c: CommodityKind, com: &mut Commodities) -> f32 {
+        let favorability = self.favorability(c, com);
+        let num_asks = favorability * self.stock_pile[&c].surplus();
         num_asks.max(1.0)
     }
-    fn find_buy_count(&mut self, c: String) -> f32 {
-        let avg_price = self
-            .com
-            .get_mut(&c)
-            .unwrap()
-            .get_avg_price(self.history_count);
-        let lowest_price = self.stock_pile.get_mut(&c).unwrap().price_history.get_min();
-        let highest_price = self.stock_pile.get_mut(&c).unwrap().price_history.get_max();
-        let mut favorability: f32 = 0.5; // TODO LERP 1.0/avg_price.lerp(lowest_price,highest_price);
-        favorability = favorability.clamp(0.0, 1.0);
-        let num_bids = (1.0 - favorability) * self.stock_pile.get_mut(&c).unwrap().deficit();
-        num_bids.max(1.0)
-    }
-    pub fn consume_com_hash(&mut self) -> TradeSubmission {
+    fn find_buy_count(&mut self, c: CommodityKind, com: &mut Commodities) -> f32 {
+        let favorability = self.favorability(c, com);
+        let deficit = self.stock_pile[&c].deficit();
+        let num_bids = (1.0 - favorability) * deficit;
+        num_bids.max(1.0).min(deficit)
+    }
+    /// Bid for everything the agent uses but doesn't make
+    pub fn consume_com_hash(&mut self, agent_idx: usize, com: &mut Commodities) -> TradeSubmission {
         let mut bids = TradeSubmission::new();
-        let keys = self.stock_pile.keys().cloned().collect::<Vec<_>>();
+        let keys = self.stock_pile.keys().copied().collect::<Vec<_>>();
         for key in keys {
             if self.buildables.contains(&key) {
                 continue;
             }
-            let num_bids = self.find_buy_count(key.clone());
-            let value = self.stock_pile.get_mut(&key).unwrap();
-
-            if num_bids > 0.0 {
-                let buy_price = value.get_price();
-                if buy_price > 1000.0 {
-                    println!(
-                        "{} buy price: {} : {}",
-                        key.clone(),
-                        buy_price,
-                        value.min_price_belief
-                    );
-                }
-                if num_bids < 0.0 {
-                    println!("{} buying negative {} for {}", key, num_bids, buy_price);
-                }
-                //TODO
-                /*
-                bids.add(
-                    key.clone(),
-                    Trade::new(value.commodity_name.clone(), buy_price, num_bids, self),
-                );*/
+            let num_bids = self.find_buy_count(key, com);
+            if num_bids >= 1.0 {
+                let buy_price = self.stock_pile.get_mut(&key).unwrap().get_price();
+                bids.add(key, Trade::new(key, buy_price, num_bids as i32, agent_idx));
             }
         }
-        /*
-        for (key,value) in self.stock_pile.iter(){
-            if self.buildables.contains(&key){
-                continue;
-            }
-            let num_bids = self.find_buy_count(key.clone());
-            if num_bids > 0.0{
-                let buy_price = value.get_price();
-                if buy_price > 1000.0{
-                    println!("{} buy price: {} : {}",key.clone(),buy_price,value.min_price_belief);
-                }
-                if num_bids < 0.0{
-                    println!("{} buying negative {} for {}",key,num_bids,buy_price);
-                }
-                bids.add(key.clone(), Trade::new(value.commodity_name.clone(),buy_price,num_bids, &*self));
+        bids
+    }
+    /// Ask for everything the agent holds, for agents that only sell on
+    /// what they are given
+    pub fn offer_stock(&mut self, agent_idx: usize) -> TradeSubmission {
+        let mut asks = TradeSubmission::new();
+        for (kind, stock) in self.stock_pile.iter_mut() {
+            if stock.quantity >= 1.0 {
+                let price = stock.get_price();
+                asks.add(
+                    *kind,
+                    Trade::new(*kind, price, stock.quantity as i32, agent_idx),
+                );
             }
         }
-        */
-        bids
+        asks
     }
-    //TODO
-    pub fn produce_com_hash(&mut self, mut idle_tax: f32) -> (f32, TradeSubmission) {
+    /// Make as much of each buildable as the agent's inputs allow and
+    /// offer it for sale. An agent that can't make anything pays an idle
+    /// tax out of its cash, which is returned with the asks.
+    pub fn produce_com_hash(
+        &mut self,
+        agent_idx: usize,
+        com: &mut Commodities,
+    ) -> (f32, TradeSubmission) {
         let mut asks = TradeSubmission::new();
+        let mut idle_tax = 0.0;
 
-        /*
-        for buildable in self.buildables.iter_mut(){
-            let mut num_produced = f32::MAX;
+        for buildable in self.buildables.clone() {
+            let recipe: Vec<(CommodityKind, f32)> = match com.get(buildable) {
+                Some(c) => c.get_dependency().iter().map(|(k, v)| (*k, *v)).collect(),
+                None => {
+                    println!("not a commodity {}", buildable.name());
+                    continue;
+                }
+            };
 
-            if(!self.com.contains_key(buildable)){
-                println!("not a commodity {}", buildable);
-            }
-            for (key,value) in com.get_mut(buildable).unwrap().dep.dependency_map.iter(){
-                let num_needed = value;
-                let num_avail = self.stock_pile.get(&*key).unwrap().quantity;
-                num_produced = num_produced.min(num_avail/num_needed);
+            let mut num_produced = f32::MAX;
+            for (key, num_needed) in &recipe {
+                let num_avail = self.get_quantity(*key);
+                num_produced = num_produced.min(num_avail / num_needed);
             }
 
-            let upper_bound = self.
-                stock_pile.get(buildable).unwrap().production_rate.min(self.stock_pile.get(buildable).unwrap().deficit());
-            num_produced = num_produced.clamp(0.0,upper_bound);
-            for (key,value) in com.get(buildable).unwrap().dep.dependency_map.iter(){
-                let stock = self.stock_pile.get(&*key).unwrap().quantity;
-                let mut num_used = value * num_produced;
-                num_used = num_used.clamp(0.0,stock);
-                self.stock_pile.get_mut(&*key).unwrap().increase_quantity(-num_used);
+            let build_stock = &self.stock_pile[&buildable];
+            let upper_bound = build_stock
+                .production_rate
+                .min(build_stock.deficit() / build_stock.production);
+            num_produced = num_produced.clamp(0.0, upper_bound.max(0.0)).floor();
+            for (key, num_needed) in &recipe {
+                self.use_up(*key, num_needed * num_produced);
             }
-            num_produced *= self.stock_pile.get(buildable).unwrap().production;
-            num_produced = num_produced.max(0.0);
-            self.stock_pile.get_mut(buildable).unwrap().increase_quantity(num_produced);
+            num_produced *= self.stock_pile[&buildable].production;
 
-            let build_stock = self.stock_pile.get_mut(buildable).unwrap();
-            //TODO look at
-            let sell_price = self.find_sell_count(buildable.to_string().clone());
-            build_stock.get_price();
+            let cost = self.get_cost_of(buildable, com);
+            let build_stock = self.stock_pile.get_mut(&buildable).unwrap();
+            build_stock.increase_quantity(num_produced);
+            build_stock.cost = cost;
 
-            if num_produced > 0.0 && sell_price > 0.0{
-                asks.add(buildable.to_string(), Trade::new(buildable.to_string(),sell_price,build_stock.quantity, &*self));
+            if num_produced <= 0.0 {
+                let tax = self.cash.abs() * IDLE_TAX_RATE;
+                self.cash -= tax;
+                idle_tax += tax;
             }
-            else{
-                idle_tax = self.cash.abs() * 0.05;
-                self.cash -= idle_tax;
+
+            if self.stock_pile[&buildable].quantity >= 1.0 {
+                let num_asks = self
+                    .find_sell_count(buildable, com)
+                    .min(self.stock_pile[&buildable].quantity);
+                let sell_price = self.stock_pile.get_mut(&buildable).unwrap().get_price();
+                asks.add(
+                    buildable,
+                    Trade::new(buildable, sell_price, num_asks as i32, agent_idx),
+                );
             }
         }
-        */
         (idle_tax, asks)
     }
-    //TODO
-    fn get_cost_of(commodity: String) -> f32 {
-        0.0
+    /// What the inputs that went into one unit of `commodity` cost the agent
+    fn get_cost_of(&self, commodity: CommodityKind, com: &Commodities) -> f32 {
+        let c = match com.get(commodity) {
+            Some(c) => c,
+            None => return 0.0,
+        };
+        let cost: f32 = c
+            .get_dependency()
+            .iter()
+            .map(|(k, v)| self.stock_pile.get(k).map_or(0.0, |s| s.mean_cost) * v)
+            .sum();
+        cost / c.get_production()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::market_house::MarketHouse;
+
+    fn farmer(seeds: f32, com: &mut Commodities) -> EconAgent {
+        let mut agent = EconAgent::new();
+        agent.init_agent(100.0, &vec![CommodityKind::CarrotCrop], seeds, 20.0, com);
+        agent
+    }
+
+    #[test]
+    fn farmer_plants_seed_and_bids_for_more() {
+        let mut com = Commodities::new();
+        com.init();
+        let mut agent = farmer(5.0, &mut com);
+        let (idle_tax, asks) = agent.produce_com_hash(0, &mut com);
+        let bids = agent.consume_com_hash(0, &mut com);
+
+        // One seed makes two crops, each costing half the seed
+        assert_eq!(idle_tax, 0.0);
+        assert_eq!(agent.get_quantity(CommodityKind::CarrotSeed), 4.0);
+        assert_eq!(agent.get_quantity(CommodityKind::CarrotCrop), 2.0);
+        assert_eq!(agent.get_cost_of(CommodityKind::CarrotCrop, &com), 1.75);
+
+        // The market price sits in the middle of what the farmer has seen,
+        // so it offers half its crops and bids for half the seed it has room for
+        let mut market = MarketHouse::new();
+        market.submit_asks(asks);
+        market.submit_bids(bids);
+        assert_eq!(market.offered(CommodityKind::CarrotCrop), (1, 0));
+        assert_eq!(market.offered(CommodityKind::CarrotSeed), (0, 8));
+    }
+
+    #[test]
+    fn farmer_without_seed_pays_idle_tax() {
+        let mut com = Commodities::new();
+        com.init();
+        let mut agent = farmer(0.0, &mut com);
+        let (idle_tax, asks) = agent.produce_com_hash(0, &mut com);
+        assert_eq!(idle_tax, 100.0 * IDLE_TAX_RATE);
+        assert_eq!(agent.get_cash(), 100.0 - idle_tax);
+        let mut market = MarketHouse::new();
+        market.submit_asks(asks);
+        assert_eq!(market.offered(CommodityKind::CarrotCrop), (0, 0));
     }
 }
@@ -0,0 +1,248 @@
+//! Headless pest evolution simulator. Breeds each pest species against
+//! a synthetic field for a number of generations, without SDL or the
+//! rest of the game, and writes the trajectory of every gene as CSV in
+//! the same format as `saves/pest_stats.csv`. Change `MUTATE_RATE`,
+//! `START_POP_SIZE` or the species' attack settings and rerun with the
+//! same seed to compare.
+//!
+//! Usage:
+//!   cargo run --bin pest_sim -- [--seed N] [--generations N]
+//!       [--field carrot=N,corn=N,potato=N,lettuce=N] [--resistance R]
+//!       [--species NAME] [--species-file PATH] [--config PATH]
+//!       [--immigration] [--out PATH]
+
+// The game's pest modules are shared with this binary, which only uses
+// part of them
+#![allow(dead_code)]
+
+#[path = "../calendar.rs"]
+mod calendar;
+#[path = "../config.rs"]
+mod config;
+#[path = "../crop_type.rs"]
+mod crop_type;
+#[path = "../immigration.rs"]
+mod immigration;
+#[path = "../pest.rs"]
+mod pest;
+#[path = "../pest_population.rs"]
+mod pest_population;
+#[path = "../pest_species.rs"]
+mod pest_species;
+#[path = "../pest_stats.rs"]
+mod pest_stats;
+#[path = "../rng.rs"]
+mod rng;
+#[path = "../selection.rs"]
+mod selection;
+
+/// Stands in for the game's crop module, which needs SDL
+mod crop {
+    pub use crate::crop_type::CropType;
+}
+
+use rand::Rng;
+use rand_distr::{Distribution, Normal};
+use std::fs::File;
+use std::io::Write;
+use std::process;
+
+use config::Config;
+use crop::CropType;
+use pest_population::{Host, PestPopulation};
+use pest_stats::GenerationStats;
+
+/// Spread of the pest resistance of the field's crops around `--resistance`
+const RESISTANCE_STD_DEV: f32 = 0.1;
+
+const USAGE: &str = "usage: pest_sim [--seed N] [--generations N] \
+[--field carrot=N,corn=N,potato=N,lettuce=N] [--resistance R] [--species NAME] \
+[--species-file PATH] [--config PATH] [--immigration] [--out PATH]";
+
+/// Settings for a run, read from the command line
+struct Options {
+    seed: u64,
+    generations: usize,
+    /// Number of fully grown crops of each type in the field
+    field: Vec<(CropType, usize)>,
+    /// Average pest resistance of the field's crops
+    resistance: f32,
+    /// Only simulate this species; every species if None
+    species: Option<String>,
+    species_file: String,
+    config_file: String,
+    /// Let pests from the regional pools wander in each generation
+    immigration: bool,
+    /// File to write the CSV to; standard output if None
+    out: Option<String>,
+}
+
+impl Options {
+    fn parse(args: &[String]) -> Result<Options, String> {
+        let mut o = Options {
+            seed: 0,
+            generations: 100,
+            field: vec![
+                (CropType::Carrot, 5),
+                (CropType::Corn, 5),
+                (CropType::Potato, 5),
+                (CropType::Lettuce, 5),
+            ],
+            resistance: 0.5,
+            species: None,
+            species_file: pest_species::SPECIES_FILE.to_string(),
+            config_file: config::CONFIG_FILE.to_string(),
+            immigration: false,
+            out: None,
+        };
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            if arg == "--immigration" {
+                o.immigration = true;
+                continue;
+            }
+            let value = args
+                .next()
+                .ok_or_else(|| format!("{} needs a value", arg))?;
+            match arg.as_str() {
+                "--seed" => o.seed = value.parse().map_err(|_| "bad --seed")?,
+                "--generations" => {
+                    o.generations = value.parse().map_err(|_| "bad --generations")?
+                }
+                "--field" => o.field = parse_field(value)?,
+                "--resistance" => o.resistance = value.parse().map_err(|_| "bad --resistance")?,
+                "--species" => o.species = Some(value.clone()),
+                "--species-file" => o.species_file = value.clone(),
+                "--config" => o.config_file = value.clone(),
+                "--out" => o.out = Some(value.clone()),
+                _ => return Err(format!("unknown option {}", arg)),
+            }
+        }
+        Ok(o)
+    }
+}
+
+/// Read a field such as `carrot=10,lettuce=4`. Crops left out aren't planted.
+fn parse_field(s: &str) -> Result<Vec<(CropType, usize)>, String> {
+    s.split(',')
+        .map(|part| {
+            let (name, count) = part
+                .split_once('=')
+                .ok_or_else(|| format!("bad field entry {}", part))?;
+            let t = match name.to_lowercase().as_str() {
+                "carrot" => CropType::Carrot,
+                "corn" => CropType::Corn,
+                "potato" => CropType::Potato,
+                "lettuce" => CropType::Lettuce,
+                _ => return Err(format!("unknown crop {}", name)),
+            };
+            let count = count
+                .parse::<usize>()
+                .map_err(|_| format!("bad count for {}", name))?;
+            Ok((t, count))
+        })
+        .collect()
+}
+
+/// Every crop in the field, each with its own pest resistance
+fn plant(o: &Options) -> Vec<Host> {
+    let normal = Normal::new(o.resistance, RESISTANCE_STD_DEV).unwrap();
+    let mut hosts = Vec::new();
+    for (t, count) in &o.field {
+        for _ in 0..*count {
+            hosts.push(Host {
+                t: *t,
+                resistance: normal.sample(&mut rng::rng()).clamp(0.0, 1.0),
+                mature: true,
+            });
+        }
+    }
+    hosts
+}
+
+/// Stands in for the game's colonies: a random feeding pest attacks
+/// every crop in the field
+fn attack_field(pest_pop: &mut PestPopulation, hosts: &[Host]) {
+    pest_pop.clear_attacks();
+    let feeders: Vec<usize> = (0..pest_pop.get_length())
+        .filter(|i| {
+            pest_pop
+                .get_species()
+                .attacks_as(pest_pop.get_pest(*i).get_stage())
+        })
+        .collect();
+    if feeders.is_empty() {
+        return;
+    }
+    for host in hosts {
+        let pest = pest_pop.get_pest(feeders[rng::rng().gen_range(0..feeders.len())]);
+        let attack = pest.damage_to(host.t) * pest_pop.get_species().preference(host.t);
+        pest_pop.record_attack(*host, attack > host.resistance);
+    }
+}
+
+/// One night in season for a population, as in the game but with every
+/// crop attacked instead of colonies, and without predators or
+/// protections
+fn generation(pest_pop: &mut PestPopulation, hosts: &[Host], immigration: bool) {
+    if immigration {
+        pest_pop.drift_pool();
+        pest_pop.immigrate();
+    }
+    if pest_pop.is_extinct() {
+        return;
+    }
+    pest_pop.develop();
+    attack_field(pest_pop, hosts);
+    pest_pop.forage();
+    pest_pop.next_generation(pest_pop.carrying_capacity(hosts));
+}
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let o = match Options::parse(&args) {
+        Ok(o) => o,
+        Err(e) => {
+            eprintln!("{}\n{}", e, USAGE);
+            process::exit(1);
+        }
+    };
+    rng::seed(o.seed);
+
+    let config = Config::load(&o.config_file);
+    let hosts = plant(&o);
+    let mut output = GenerationStats::csv_header();
+    for species in pest_species::load_all(&o.species_file) {
+        if o.species
+            .as_ref()
+            .map_or(false, |s| s != species.get_name())
+        {
+            continue;
+        }
+        let name = species.get_name().to_string();
+        let mut pest_pop =
+            PestPopulation::new(species, selection::from_config(config.get("selection")));
+        pest_pop.fill_pest_population();
+        for _ in 0..o.generations {
+            generation(&mut pest_pop, &hosts, o.immigration);
+        }
+        for (i, stats) in pest_pop.get_stats().iter().enumerate() {
+            output.push_str(&stats.to_csv(&name, i));
+        }
+        eprintln!(
+            "{}: {} pests after {} generations",
+            name,
+            pest_pop.get_length(),
+            pest_pop.get_stats().len()
+        );
+    }
+
+    match &o.out {
+        Some(path) => {
+            let mut file = File::create(path).expect("Couldn't create output file");
+            file.write_all(output.as_bytes())
+                .expect("Couldn't write output file");
+        }
+        None => print!("{}", output),
+    }
+}
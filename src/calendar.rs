@@ -0,0 +1,148 @@
+//! Module for the calendar of days, seasons and years. The date moves
+//! forward each time the player sleeps.
+
+use std::fmt;
+use std::str::FromStr;
+
+/// Number of days in each season
+pub const DAYS_PER_SEASON: u32 = 28;
+
+/// Season enum
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum Season {
+    Spring,
+    Summer,
+    Fall,
+    Winter,
+}
+
+impl Season {
+    /// The season that follows this one
+    pub fn next(&self) -> Season {
+        match self {
+            Season::Spring => Season::Summer,
+            Season::Summer => Season::Fall,
+            Season::Fall => Season::Winter,
+            Season::Winter => Season::Spring,
+        }
+    }
+}
+
+impl fmt::Display for Season {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Season::Spring => write!(f, "Spring"),
+            Season::Summer => write!(f, "Summer"),
+            Season::Fall => write!(f, "Fall"),
+            Season::Winter => write!(f, "Winter"),
+        }
+    }
+}
+
+impl FromStr for Season {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "Spring" => Ok(Season::Spring),
+            "Summer" => Ok(Season::Summer),
+            "Fall" => Ok(Season::Fall),
+            "Winter" => Ok(Season::Winter),
+            _ => Err(format!("invalid Season: {}", s)),
+        }
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct Calendar {
+    /// Day of the season, from 1 to `DAYS_PER_SEASON`
+    day: u32,
+    season: Season,
+    /// Year, starting from 1
+    year: u32,
+}
+
+impl Calendar {
+    /// The first day of spring in the first year
+    pub fn new() -> Calendar {
+        Calendar {
+            day: 1,
+            season: Season::Spring,
+            year: 1,
+        }
+    }
+
+    /// Move on to the next day. Returns true if a new season started.
+    pub fn advance_day(&mut self) -> bool {
+        if self.day < DAYS_PER_SEASON {
+            self.day += 1;
+            return false;
+        }
+        self.day = 1;
+        self.season = self.season.next();
+        if self.season == Season::Spring {
+            self.year += 1;
+        }
+        true
+    }
+
+    pub fn get_season(&self) -> Season {
+        self.season
+    }
+
+    /// Date formatted as e.g. "Spring 3 Year 1"
+    pub fn date_string(&self) -> String {
+        format!("{} {} Year {}", self.season, self.day, self.year)
+    }
+
+    pub fn to_save_string(&self) -> String {
+        format!("date;{};{};{}\n", self.day, self.season, self.year)
+    }
+
+    /// Parse a `date;<day>;<season>;<year>` line, already split on `;`
+    pub fn from_save_string(s: &[&str]) -> Calendar {
+        Calendar {
+            day: s[1].parse::<u32>().unwrap(),
+            season: s[2].parse::<Season>().unwrap(),
+            year: s[3].parse::<u32>().unwrap(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn season_changes_after_last_day() {
+        let mut cal = Calendar::new();
+        for _ in 1..DAYS_PER_SEASON {
+            assert!(!cal.advance_day());
+        }
+        assert_eq!(cal.day, DAYS_PER_SEASON);
+        assert!(cal.advance_day());
+        assert_eq!(cal.day, 1);
+        assert_eq!(cal.get_season(), Season::Summer);
+    }
+
+    #[test]
+    fn year_changes_after_winter() {
+        let mut cal = Calendar::new();
+        for _ in 0..DAYS_PER_SEASON * 4 {
+            cal.advance_day();
+        }
+        assert_eq!(cal.get_season(), Season::Spring);
+        assert_eq!(cal.year, 2);
+    }
+
+    #[test]
+    fn save_string_round_trips() {
+        let mut cal = Calendar::new();
+        for _ in 0..40 {
+            cal.advance_day();
+        }
+        let s = cal.to_save_string();
+        let results: Vec<&str> = s.trim_end().split(";").collect();
+        assert_eq!(Calendar::from_save_string(&results), cal);
+    }
+}
@@ -0,0 +1,192 @@
+//! Module for the world clock. Time runs in real time while the
+//! player is out and about, and each day ends by sleeping or by
+//! passing out at 2 AM.
+
+use sdl2::pixels::Color;
+use sdl2::rect::Rect;
+use sdl2::render::{Texture, WindowCanvas};
+use std::time::{Duration, Instant};
+
+use crate::calendar::Calendar;
+use crate::hud;
+use crate::CAM_W;
+
+/// Real time it takes for one in-game minute to pass
+const REAL_TIME_PER_MINUTE: Duration = Duration::from_millis(700);
+/// The day starts at 6 AM
+pub const DAY_START: u32 = 6 * 60;
+/// The player passes out at 2 AM the next morning
+pub const PASS_OUT_TIME: u32 = 26 * 60;
+/// Minutes a farming action (tilling, watering, planting, harvesting) takes
+pub const ACTION_MINUTES: u32 = 5;
+/// Minutes a purchase at a store takes
+pub const SHOPPING_MINUTES: u32 = 30;
+/// Minutes it takes to travel between the farm and the market
+pub const TRAVEL_MINUTES: u32 = 60;
+/// Stores open at 9 AM...
+pub const STORE_OPEN: u32 = 9 * 60;
+/// ...and close at 5 PM
+pub const STORE_CLOSE: u32 = 17 * 60;
+/// Darkness starts to fall at 6 PM
+const DUSK: u32 = 18 * 60;
+/// and is at its darkest by 10 PM
+const FULL_DARK: u32 = 22 * 60;
+/// Alpha of the night overlay when it is darkest
+const MAX_DARKNESS: u32 = 170;
+
+/// Phase enum
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum DayPhase {
+    Morning,
+    Afternoon,
+    Evening,
+    Night,
+}
+
+impl std::fmt::Display for DayPhase {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            DayPhase::Morning => write!(f, "Morning"),
+            DayPhase::Afternoon => write!(f, "Afternoon"),
+            DayPhase::Evening => write!(f, "Evening"),
+            DayPhase::Night => write!(f, "Night"),
+        }
+    }
+}
+
+pub struct WorldClock {
+    /// Minutes since midnight at the start of the current day. Goes past
+    /// 24 * 60 after midnight, up to `PASS_OUT_TIME`.
+    minutes: u32,
+    /// Real time that hasn't been turned into in-game minutes yet
+    carry: Duration,
+    /// When the clock was last ticked
+    last_tick: Instant,
+    /// Today's date
+    calendar: Calendar,
+}
+
+impl WorldClock {
+    pub fn new(calendar: Calendar) -> WorldClock {
+        WorldClock {
+            minutes: DAY_START,
+            carry: Duration::ZERO,
+            last_tick: Instant::now(),
+            calendar,
+        }
+    }
+
+    /// Advance the clock by the real time passed since the last tick.
+    /// While `paused` (i.e. a menu is up) time doesn't pass.
+    pub fn tick(&mut self, paused: bool) {
+        let now = Instant::now();
+        if !paused {
+            self.carry += now - self.last_tick;
+            while self.carry >= REAL_TIME_PER_MINUTE {
+                self.carry -= REAL_TIME_PER_MINUTE;
+                self.advance(1);
+            }
+        }
+        self.last_tick = now;
+    }
+
+    /// Skip ahead some minutes, e.g. because an action took time
+    pub fn advance(&mut self, minutes: u32) {
+        self.minutes = (self.minutes + minutes).min(PASS_OUT_TIME);
+    }
+
+    /// Reset the clock to the morning after sleeping and turn the
+    /// calendar to the next day. Returns true if a new season started.
+    pub fn start_new_day(&mut self) -> bool {
+        self.minutes = DAY_START;
+        self.carry = Duration::ZERO;
+        self.last_tick = Instant::now();
+        self.calendar.advance_day()
+    }
+
+    pub fn get_calendar(&self) -> &Calendar {
+        &self.calendar
+    }
+
+    pub fn get_phase(&self) -> DayPhase {
+        match self.minutes {
+            m if m < 12 * 60 => DayPhase::Morning,
+            m if m < 17 * 60 => DayPhase::Afternoon,
+            m if m < 21 * 60 => DayPhase::Evening,
+            _ => DayPhase::Night,
+        }
+    }
+
+    /// True once it is 2 AM and the player hasn't gone to bed
+    pub fn passed_out(&self) -> bool {
+        self.minutes >= PASS_OUT_TIME
+    }
+
+    pub fn store_open(&self) -> bool {
+        self.minutes >= STORE_OPEN && self.minutes < STORE_CLOSE
+    }
+
+    /// Alpha of the darkening overlay for the current time
+    pub fn darkness(&self) -> u8 {
+        if self.minutes <= DUSK {
+            0
+        } else if self.minutes >= FULL_DARK {
+            MAX_DARKNESS as u8
+        } else {
+            ((self.minutes - DUSK) * MAX_DARKNESS / (FULL_DARK - DUSK)) as u8
+        }
+    }
+
+    /// Time formatted as e.g. "6:40 AM"
+    pub fn time_string(&self) -> String {
+        let (hour, suffix) = twelve_hour(self.minutes);
+        format!("{}:{:02} {}", hour, self.minutes % 60, suffix)
+    }
+
+    /// Draw the night overlay over the whole screen
+    pub fn draw_overlay(&self, wincan: &mut WindowCanvas, r: Rect) {
+        let alpha = self.darkness();
+        if alpha > 0 {
+            wincan.set_draw_color(Color::RGBA(10, 10, 40, alpha));
+            wincan.fill_rect(r).unwrap();
+        }
+    }
+
+    /// Draw the date, time and phase in the top right corner in `font`
+    pub fn draw(&self, wincan: &mut WindowCanvas, font: &Texture) {
+        let lines = vec![
+            self.calendar.date_string(),
+            self.time_string(),
+            self.get_phase().to_string(),
+        ];
+        let width = lines
+            .iter()
+            .map(|l| hud::text_width(l, 4))
+            .max()
+            .unwrap_or(0);
+        hud::draw_panel(
+            wincan,
+            font,
+            &lines,
+            CAM_W as i32 - width as i32 - 30,
+            10,
+            4,
+        );
+    }
+}
+
+/// Minutes since midnight formatted as a whole hour, e.g. "9 AM"
+pub fn hour_string(minutes: u32) -> String {
+    let (hour, suffix) = twelve_hour(minutes);
+    format!("{} {}", hour, suffix)
+}
+
+/// 12-hour clock hour and AM/PM for minutes since midnight
+fn twelve_hour(minutes: u32) -> (u32, &'static str) {
+    let hour = (minutes / 60) % 24;
+    let suffix = if hour < 12 { "AM" } else { "PM" };
+    match hour % 12 {
+        0 => (12, suffix),
+        h => (h, suffix),
+    }
+}
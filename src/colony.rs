@@ -0,0 +1,358 @@
+//! Module for pest colonies living on the farm's tiles. Colonies land
+//! on crops on their species' bug nights, grow on crops they beat and lay
+//! eggs on the crops next to them, so an infestation can be stopped by
+//! pulling up the plants it has reached. Eggs wait in the soil, even
+//! through the winter, until they hatch.
+
+use rand::Rng;
+use sdl2::rect::Rect;
+
+use crate::calendar::Season;
+use crate::crop::CropType;
+use crate::genes;
+use crate::pest::{Pest, PestGeneType, Stage};
+use crate::pest_population::{Host, PestPopulation};
+use crate::pest_species::Behavior;
+use crate::pesticide;
+use crate::population::Population;
+use crate::protection;
+use crate::protection::{ProtectionKind, Protections};
+use crate::TILE_SIZE;
+
+/// Adult colonies this big or bigger lay eggs on the tiles around them.
+/// Swarming species lay at any size.
+pub const SPREAD_SIZE: u32 = 2;
+/// Health a crop loses per pest in the colony for each point its
+/// attack beats the crop's pest resistance by
+const DAMAGE_PER_PEST: f32 = 0.5;
+/// Colonies stop growing at this size
+pub const MAX_SIZE: u32 = 6;
+
+pub struct Colony {
+    /// Index of the colony's species' population
+    species: usize,
+    /// Rough number of pests living on the tile
+    size: u32,
+    /// Genes shared by the colony's pests
+    pest: Pest,
+}
+
+impl Colony {
+    /// A new colony of a single pest of the species at index `species`
+    pub fn new(species: usize, pest: Pest) -> Colony {
+        Colony {
+            species,
+            size: 1,
+            pest,
+        }
+    }
+
+    pub fn get_species(&self) -> usize {
+        self.species
+    }
+
+    pub fn get_size(&self) -> u32 {
+        self.size
+    }
+
+    pub fn get_pest(&self) -> &Pest {
+        &self.pest
+    }
+
+    /// A predator eats one of the colony's pests. Returns true if none are left.
+    pub fn lose_pest(&mut self) -> bool {
+        self.size = self.size.saturating_sub(1);
+        self.size == 0
+    }
+
+    /// Rectangle of `pest_colony.png` to draw for this colony's size
+    pub fn src(&self) -> Rect {
+        let frame = match self.size {
+            0..=1 => 0,
+            2..=3 => 1,
+            _ => 2,
+        };
+        Rect::new(frame * TILE_SIZE as i32, 0, TILE_SIZE, TILE_SIZE)
+    }
+
+    /// Generates a save string for the colony on tile (x, y)
+    pub fn to_save_string(&self, x: u32, y: u32, pest_pops: &[PestPopulation]) -> String {
+        format!(
+            "colony;{};{};{};{};{}",
+            x,
+            y,
+            self.size,
+            pest_pops[self.species].get_species().get_name(),
+            self.pest.to_save_string()
+        )
+    }
+
+    /// Creates a colony from a save string, already split on `;`.
+    /// Returns the colony's tile along with it, or None if its
+    /// species no longer exists.
+    pub fn from_save_string(
+        v: &[&str],
+        pest_pops: &[PestPopulation],
+    ) -> Option<(u32, u32, Colony)> {
+        let species = pest_pops
+            .iter()
+            .position(|p| p.get_species().get_name() == v[4])?;
+        Some((
+            v[1].parse::<u32>().unwrap(),
+            v[2].parse::<u32>().unwrap(),
+            Colony {
+                species,
+                size: v[3].parse::<u32>().unwrap(),
+                pest: Pest::from_save_string(v[5..].to_vec()),
+            },
+        ))
+    }
+}
+
+/// Returns true if (x, y) holds a crop pests can live on
+fn is_host(pop: &Population, x: u32, y: u32) -> bool {
+    let c = pop.get_crop_with_index(x, y);
+    c.get_crop_type_enum() != CropType::None && !c.rotten()
+}
+
+/// Every crop on the farm pests can live on
+pub fn host_crops(pop: &Population) -> Vec<Host> {
+    let mut hosts = Vec::new();
+    for x in 0..pop.width() {
+        for y in 0..pop.height() {
+            if is_host(pop, x, y) {
+                let c = pop.get_crop_with_index(x, y);
+                hosts.push(Host {
+                    t: c.get_crop_type_enum(),
+                    resistance: c.get_gene(genes::GeneType::PestResistance).unwrap_or(0.0),
+                    mature: c.get_stage() == 3,
+                });
+            }
+        }
+    }
+    hosts
+}
+
+/// Pests from the population at index `species` land on a few random
+/// crops, never under a row cover, in the stage their species arrives
+/// in. Birds won't land near a scarecrow.
+pub fn arrive(
+    pop: &mut Population,
+    pest_pops: &[PestPopulation],
+    species: usize,
+    protections: &Protections,
+) {
+    let pest_pop = &pest_pops[species];
+    let bird = pest_pop.get_species().get_behavior() == Behavior::Peck;
+    let mut hosts = Vec::new();
+    for x in 0..pop.width() {
+        for y in 0..pop.height() {
+            if is_host(pop, x, y)
+                && pop.get_colony(x, y).is_none()
+                && !protections.covers(ProtectionKind::RowCover, x, y)
+                && !(bird && protections.covers(ProtectionKind::Scarecrow, x, y))
+            {
+                hosts.push((x, y));
+            }
+        }
+    }
+    let mut rng = rand::thread_rng();
+    for _ in 0..pest_pop.get_species().get_arrivals() {
+        if hosts.is_empty() || pest_pop.get_length() == 0 {
+            return;
+        }
+        let (x, y) = hosts.swap_remove(rng.gen_range(0..hosts.len()));
+        let pest = pest_pop.get_pest(rng.gen_range(0..pest_pop.get_length()));
+        pop.set_colony(
+            x,
+            y,
+            Some(Colony::new(
+                species,
+                pest.clone()
+                    .with_stage(pest_pop.get_species().arrival_stage()),
+            )),
+        );
+    }
+}
+
+/// Nightly step for every colony on the farm. Eggs hatch once their
+/// species is in `season`. Each colony that feeds at its stage attacks
+/// the crop it lives on, growing and wearing down the crop's health if
+/// it beats the crop's pest resistance and shrinking if it doesn't; the
+/// crop rots once its health is gone. Then big adult colonies lay eggs on
+/// neighboring crops. How each colony attacks and spreads depends on its
+/// species, and colonies on sprayed crops are killed unless they resist
+/// the pesticide. `protections` scare off birds, trap adults and keep
+/// colonies from feeding or laying under row covers. Every attack is
+/// recorded in its species' population, which breeds from them.
+pub fn night(
+    pop: &mut Population,
+    pest_pops: &mut [PestPopulation],
+    season: Season,
+    protections: &Protections,
+) {
+    let mut rng = rand::thread_rng();
+    let mut spreads = Vec::new();
+    let mut hatched = Vec::new();
+    for pest_pop in pest_pops.iter_mut() {
+        pest_pop.clear_attacks();
+    }
+
+    for x in 0..pop.width() {
+        for y in 0..pop.height() {
+            let colony = match pop.get_colony(x, y) {
+                Some(colony) => colony,
+                None => continue,
+            };
+            // Eggs sit safe in the soil, whatever is growing above them
+            if colony.pest.get_stage() == Stage::Egg {
+                if pest_pops[colony.species].get_species().spawns_in(season)
+                    && pop.get_colony_mut(x, y).unwrap().pest.develop()
+                {
+                    hatched.push((x, y));
+                }
+                continue;
+            }
+            // Nothing left to eat; the colony moves on or starves
+            if !is_host(pop, x, y) {
+                pop.set_colony(x, y, None);
+                continue;
+            }
+
+            // Spraying kills the colony before it can attack
+            if pop.get_pesticide(x, y) > 0 {
+                let colony = pop.get_colony(x, y).unwrap();
+                let resistance = colony.pest.get_pest_gene(PestGeneType::PesticideResistance);
+                if pesticide::kills(&mut pest_pops[colony.species], resistance) {
+                    pop.set_colony(x, y, None);
+                    continue;
+                }
+            }
+
+            // Scarecrows scare birds off, and sticky traps catch adults
+            let colony = pop.get_colony(x, y).unwrap();
+            let behavior = pest_pops[colony.species].get_species().get_behavior();
+            if behavior == Behavior::Peck && protections.covers(ProtectionKind::Scarecrow, x, y) {
+                pop.set_colony(x, y, None);
+                continue;
+            }
+            if colony.pest.get_stage() == Stage::Adult
+                && protections.covers(ProtectionKind::StickyTrap, x, y)
+                && rng.gen::<f32>() < protection::TRAP_CATCH
+                && pop.get_colony_mut(x, y).unwrap().lose_pest()
+            {
+                pop.set_colony(x, y, None);
+                continue;
+            }
+
+            // Larvae grow up, and only stages that feed attack
+            let colony = pop.get_colony_mut(x, y).unwrap();
+            colony.pest.develop();
+            let stage = colony.pest.get_stage();
+            if !pest_pops[colony.species].get_species().attacks_as(stage) {
+                continue;
+            }
+
+            let c = pop.get_crop_with_index(x, y);
+            let colony = pop.get_colony(x, y).unwrap();
+            let species = pest_pops[colony.species].get_species();
+            let mut resistance = c.get_gene(genes::GeneType::PestResistance).unwrap_or(0.0);
+            if species.get_behavior() == Behavior::Burrow {
+                resistance /= 2.0;
+            }
+            // Pests under a row cover can't get at the crop
+            let covered = protections.covers(ProtectionKind::RowCover, x, y);
+            let attack = if covered {
+                0.0
+            } else {
+                colony.pest.damage_to(c.get_crop_type_enum())
+                    * species.preference(c.get_crop_type_enum())
+            };
+            if !covered {
+                let host = Host {
+                    t: c.get_crop_type_enum(),
+                    resistance,
+                    mature: c.get_stage() == 3,
+                };
+                pest_pops[colony.species].record_attack(host, attack > resistance);
+            }
+            let colony = pop.get_colony_mut(x, y).unwrap();
+            if attack > resistance {
+                colony.size = (colony.size + 1).min(MAX_SIZE);
+            } else {
+                colony.size -= 1;
+            }
+            let size = colony.size;
+
+            if size == 0 {
+                pop.set_colony(x, y, None);
+                continue;
+            }
+            // The crop loses health by how far the attack beat its
+            // resistance, for every pest in the colony
+            let damage = (attack - resistance).max(0.0) * size as f32 * DAMAGE_PER_PEST;
+            let c = pop.get_crop_with_index_mut(x, y);
+            if c.damage(damage) {
+                // Change a crop to show the rotten sprite
+                c.set_rotten(true);
+                c.set_stage(0);
+                c.set_water(false);
+                c.set_genes(None);
+                continue;
+            }
+            // Birds peck and fly off without laying on the farm
+            if stage == Stage::Adult
+                && behavior != Behavior::Peck
+                && (size >= SPREAD_SIZE || behavior == Behavior::Swarm)
+            {
+                let colony = pop.get_colony(x, y).unwrap();
+                let mut breed_speed = colony.pest.get_pest_gene(PestGeneType::BreedSpeed);
+                if behavior == Behavior::Burrow {
+                    breed_speed /= 2.0;
+                }
+                for (dx, dy) in [(-1, 0), (1, 0), (0, -1), (0, 1)] {
+                    let (nx, ny) = (x as i32 + dx, y as i32 + dy);
+                    if pop.in_bounds(nx, ny) && rng.gen::<f32>() < breed_speed {
+                        spreads.push((
+                            nx as u32,
+                            ny as u32,
+                            colony.species,
+                            colony.pest.clone().with_stage(Stage::Egg),
+                        ));
+                    }
+                }
+            }
+        }
+    }
+
+    // Lay eggs after every colony has had its turn
+    for (x, y, species, pest) in spreads {
+        if is_host(pop, x, y)
+            && pop.get_colony(x, y).is_none()
+            && !protections.covers(ProtectionKind::RowCover, x, y)
+        {
+            pop.set_colony(x, y, Some(Colony::new(species, pest)));
+        }
+    }
+
+    // Larvae that hatch under a dead or cleared crop crawl to a crop next
+    // to it, or starve if there isn't one free
+    for (x, y) in hatched {
+        if is_host(pop, x, y) {
+            continue;
+        }
+        let colony = pop.get_colony_mut(x, y).unwrap();
+        let larvae = Colony::new(colony.species, colony.pest.clone());
+        pop.set_colony(x, y, None);
+        for (dx, dy) in [(-1, 0), (1, 0), (0, -1), (0, 1)] {
+            let (nx, ny) = (x as i32 + dx, y as i32 + dy);
+            if pop.in_bounds(nx, ny)
+                && is_host(pop, nx as u32, ny as u32)
+                && pop.get_colony(nx as u32, ny as u32).is_none()
+            {
+                pop.set_colony(nx as u32, ny as u32, Some(larvae));
+                break;
+            }
+        }
+    }
+}
@@ -1,6 +1,12 @@
+use crate::crop_type::CropType;
 use std::collections::HashMap;
 
-#[derive(Copy, Clone, PartialEq, Debug)]
+/// Crops a farmer grows from each seed
+const CROPS_PER_SEED: f32 = 2.0;
+/// Seeds a seed grower saves from each crop
+const SEEDS_PER_CROP: f32 = 3.0;
+
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
 pub enum CommodityKind {
     CarrotSeed = 0,
     CarrotCrop = 1,
@@ -14,6 +20,42 @@ pub enum CommodityKind {
 
 impl CommodityKind {
     pub const SIZE: usize = 8;
+    /// Every commodity, in the order of their values
+    pub const ALL: [CommodityKind; CommodityKind::SIZE] = [
+        CommodityKind::CarrotSeed,
+        CommodityKind::CarrotCrop,
+        CommodityKind::CornSeed,
+        CommodityKind::CornCrop,
+        CommodityKind::PotatoSeed,
+        CommodityKind::PotatoCrop,
+        CommodityKind::LettuceSeed,
+        CommodityKind::LettuceCrop,
+    ];
+
+    /// Commodity traded for crops, or seeds if `seed`, of type `t`
+    pub fn of_crop(t: CropType, seed: bool) -> Option<CommodityKind> {
+        let (s, c) = match t {
+            CropType::None => return None,
+            CropType::Carrot => (CommodityKind::CarrotSeed, CommodityKind::CarrotCrop),
+            CropType::Corn => (CommodityKind::CornSeed, CommodityKind::CornCrop),
+            CropType::Potato => (CommodityKind::PotatoSeed, CommodityKind::PotatoCrop),
+            CropType::Lettuce => (CommodityKind::LettuceSeed, CommodityKind::LettuceCrop),
+        };
+        Some(if seed { s } else { c })
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            CommodityKind::CarrotSeed => "CarrotSeed",
+            CommodityKind::CarrotCrop => "CarrotCrop",
+            CommodityKind::CornSeed => "CornSeed",
+            CommodityKind::CornCrop => "CornCrop",
+            CommodityKind::PotatoSeed => "PotatoSeed",
+            CommodityKind::PotatoCrop => "PotatoCrop",
+            CommodityKind::LettuceSeed => "LettuceSeed",
+            CommodityKind::LettuceCrop => "LettuceCrop",
+        }
+    }
 }
 
 pub struct ESList {
@@ -27,28 +69,19 @@ impl ESList {
             eslist: Vec::new(),
         }
     }
+    /// Average of the last `history` values
     pub fn last_average(&mut self, history: i32) -> f32 {
         if self.eslist.len() == 0 {
             return 0.0;
         }
-        let skip =
-            ((self.eslist.len() as i32) - 1).min(0.max((self.eslist.len() as i32) - history));
-        let end = ((self.eslist.len() as i32) - 1).min(history);
-        if skip < end {
-            let mut sum_val = 0.0;
-            for x in skip..end {
-                sum_val += self.eslist[x as usize];
-            }
-            self.avg = sum_val / ((end - skip) as f32);
-        }
+        let skip = (self.eslist.len() as i32 - history.max(1)).max(0) as usize;
+        let recent = &self.eslist[skip..];
+        self.avg = recent.iter().sum::<f32>() / recent.len() as f32;
         self.avg
     }
     pub fn add(&mut self, val: f32) {
         self.eslist.push(val);
     }
-    pub fn count(&mut self) -> i32 {
-        self.eslist.len() as i32
-    }
 }
 
 pub struct Commodity {
@@ -57,17 +90,17 @@ pub struct Commodity {
     prices: ESList,
     trades: ESList,
     profits: ESList,
-    avg_price: f32,
-    first_avg_price: bool,
     debug: i32,
-    name: String,
+    kind: CommodityKind,
     price: f32,
     demand: f32,
     production: f32,
     dep: Dependency,
 }
 impl Commodity {
-    pub fn new(n: String, p: f32, d: Dependency) -> Commodity {
+    /// Commodity `k`, which agents make `p` of from each batch of the
+    /// inputs in `d`, first traded at `start_price`
+    pub fn new(k: CommodityKind, p: f32, d: Dependency, start_price: f32) -> Commodity {
         let mut _bids = ESList::new();
         let mut _asks = ESList::new();
         let mut _prices = ESList::new();
@@ -76,7 +109,7 @@ impl Commodity {
 
         _bids.add(1.0);
         _asks.add(1.0);
-        _prices.add(1.0);
+        _prices.add(start_price);
         _trades.add(1.0);
         _profits.add(1.0);
 
@@ -86,49 +119,65 @@ impl Commodity {
             prices: _prices,
             trades: _trades,
             profits: _profits,
-            avg_price: 1.0,
-            first_avg_price: true,
             debug: 0,
-            name: n,
-            price: 1.0,
+            kind: k,
+            price: start_price,
             demand: 1.0,
             production: p,
             dep: d,
         }
     }
-    pub fn get_avg_price(&mut self, history: i32) -> f32 {
-        if self.first_avg_price {
-            self.first_avg_price = false;
-            let skip = (self.prices.count() - history).max(0);
-            let end = self.prices.count();
-            let prices_after_skip: Vec<f32> = self.prices.eslist[(skip as usize)..].to_vec();
-            let prices_after_skip_len = prices_after_skip.len() as i32;
-            let mut sum_val = 0.0;
-            for x in skip..end {
-                sum_val += self.prices.eslist[x as usize];
-            }
-            self.avg_price = sum_val / ((end - skip) as f32);
+    /// Average of the last `history` prices the commodity cleared at
+    pub fn get_avg_price(&self, history: i32) -> f32 {
+        let recent = self.recent_prices(history.max(1));
+        if recent.is_empty() {
+            return self.price;
         }
-        self.avg_price
+        recent.iter().sum::<f32>() / recent.len() as f32
     }
+    /// Record the price the commodity cleared at and the demand for it
     pub fn update(&mut self, p: f32, dem: f32) {
-        self.first_avg_price = true;
         self.price = p;
+        self.prices.add(p);
         self.demand = dem;
     }
     pub fn get_price(&mut self) -> f32 {
         self.price
     }
+    /// The last `history` prices the commodity cleared at, oldest first
+    pub fn recent_prices(&self, history: i32) -> &[f32] {
+        let skip = (self.prices.eslist.len() as i32 - history).max(0) as usize;
+        &self.prices.eslist[skip..]
+    }
+    /// Replace the prices the commodity has cleared at, e.g. with ones
+    /// from a save
+    pub fn set_prices(&mut self, prices: &[f32]) {
+        if let Some(last) = prices.last() {
+            self.prices.eslist = prices.to_vec();
+            self.price = *last;
+        }
+    }
     pub fn add_bid(&mut self, new_val: f32) {
         self.bids.add(new_val);
     }
     pub fn add_ask(&mut self, new_val: f32) {
         self.asks.add(new_val);
     }
+    pub fn get_production(&self) -> f32 {
+        self.production
+    }
+    pub fn get_dependency(&self) -> &Dependency {
+        &self.dep
+    }
+    /// How many more units were bid for than asked for, on average over
+    /// the last `history` rounds. Above 1 means buyers outnumber sellers.
+    pub fn demand_ratio(&mut self, history: i32) -> f32 {
+        self.bids.last_average(history) / self.asks.last_average(history).max(1.0)
+    }
 }
 
 pub struct Dependency {
-    dependency_map: HashMap<String, f32>,
+    dependency_map: HashMap<CommodityKind, f32>,
 }
 
 impl Dependency {
@@ -137,13 +186,17 @@ impl Dependency {
             dependency_map: HashMap::new(),
         }
     }
-    pub fn add(&mut self, name: String, quantity: f32) {
-        self.dependency_map.insert(name, quantity);
+    pub fn add(&mut self, kind: CommodityKind, quantity: f32) {
+        self.dependency_map.insert(kind, quantity);
+    }
+    /// Each input and how many of it one batch needs
+    pub fn iter(&self) -> impl Iterator<Item = (&CommodityKind, &f32)> {
+        self.dependency_map.iter()
     }
 }
 
 pub struct Commodities {
-    com: HashMap<String, Commodity>,
+    com: HashMap<CommodityKind, Commodity>,
 }
 impl Commodities {
     pub fn new() -> Commodities {
@@ -154,14 +207,47 @@ impl Commodities {
     fn awake(&mut self) {
         //instance = self;
         self.com = HashMap::new();
-        //self.init();
+        self.init();
     }
-    pub fn add(&mut self, name: String, production: f32, dep: Dependency) -> bool {
-        if self.com.contains_key(&name) {
+    /// Register every commodity with its recipe and the price it starts
+    /// trading at. Seeds are grown from crops and crops from seeds.
+    pub fn init(&mut self) {
+        let crops = [
+            (CommodityKind::CarrotSeed, CommodityKind::CarrotCrop, 3.5),
+            (CommodityKind::CornSeed, CommodityKind::CornCrop, 2.5),
+            (CommodityKind::PotatoSeed, CommodityKind::PotatoCrop, 4.5),
+            (CommodityKind::LettuceSeed, CommodityKind::LettuceCrop, 6.5),
+        ];
+        for (seed, crop, seed_price) in crops {
+            let mut seed_dep = Dependency::new();
+            seed_dep.add(crop, 1.0);
+            self.add(seed, SEEDS_PER_CROP, seed_dep, seed_price);
+            let mut crop_dep = Dependency::new();
+            crop_dep.add(seed, 1.0);
+            self.add(crop, CROPS_PER_SEED, crop_dep, seed_price * 2.0);
+        }
+    }
+    pub fn add(
+        &mut self,
+        kind: CommodityKind,
+        production: f32,
+        dep: Dependency,
+        start_price: f32,
+    ) -> bool {
+        if self.com.contains_key(&kind) {
             return false;
         }
         self.com
-            .insert(name.clone(), Commodity::new(name.clone(), production, dep));
+            .insert(kind, Commodity::new(kind, production, dep, start_price));
         true
     }
+    pub fn contains(&self, kind: CommodityKind) -> bool {
+        self.com.contains_key(&kind)
+    }
+    pub fn get(&self, kind: CommodityKind) -> Option<&Commodity> {
+        self.com.get(&kind)
+    }
+    pub fn get_mut(&mut self, kind: CommodityKind) -> Option<&mut Commodity> {
+        self.com.get_mut(&kind)
+    }
 }
@@ -0,0 +1,42 @@
+//! Module for game settings read from `saves/config.txt`. Each line is
+//! a setting name followed by its `;`-separated values. Blank lines and
+//! lines starting with `#` are ignored.
+
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::Read;
+
+/// File the settings are read from
+pub const CONFIG_FILE: &str = "saves/config.txt";
+
+pub struct Config {
+    settings: HashMap<String, Vec<String>>,
+}
+
+impl Config {
+    /// Read the settings in the file at `path`. A missing file leaves
+    /// every setting at its default.
+    pub fn load(path: &str) -> Config {
+        let mut contents = String::new();
+        if let Ok(mut file) = File::open(path) {
+            file.read_to_string(&mut contents)
+                .expect("Can't read config file");
+        }
+        let mut settings = HashMap::new();
+        for line in contents.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with("#") {
+                continue;
+            }
+            let mut results = line.split(";").map(String::from);
+            let key = results.next().unwrap();
+            settings.insert(key, results.collect());
+        }
+        Config { settings }
+    }
+
+    /// Values of the setting `key`, or an empty list if it isn't set
+    pub fn get(&self, key: &str) -> &[String] {
+        self.settings.get(key).map_or(&[], |v| v.as_slice())
+    }
+}
@@ -1,9 +1,11 @@
 // Imports
 use sdl2::rect::Rect;
 use sdl2::render::{Texture, WindowCanvas};
-use std::str::FromStr;
 
+use crate::colony::Colony;
+pub use crate::crop_type::CropType;
 use crate::genes;
+use crate::pest::Pest;
 use crate::population::Population;
 use crate::InventoryItemTrait;
 
@@ -13,16 +15,10 @@ use crate::{CAM_H, CAM_W, TILE_SIZE};
 
 use rand::Rng;
 
-/// Crop type enum
-#[derive(Copy, Clone, PartialEq, Debug)]
-
-pub enum CropType {
-    None,
-    Carrot,
-    Corn,
-    Potato,
-    Lettuce,
-}
+/// Health of an undamaged crop
+pub const MAX_HEALTH: f32 = 1.0;
+/// Health a watered crop recovers each night
+const HEAL_RATE: f32 = 0.05;
 
 /// Crop struct
 pub struct Crop<'a> {
@@ -46,6 +42,12 @@ pub struct Crop<'a> {
     pollinated: bool,
     rotten: bool,
     child: Option<genes::Genes>,
+    /// How healthy the crop is, from 0 to `MAX_HEALTH`. Pests wear it
+    /// down and it rots at 0.
+    health: f32,
+    /// A pest egg hiding in a seed, with the index of its species'
+    /// population. It is laid in the soil when the seed is planted.
+    eggs: Option<(usize, Pest)>,
 }
 
 impl<'a> Crop<'a> {
@@ -89,6 +91,8 @@ impl<'a> Crop<'a> {
             pollinated: false,
             rotten: false,
             child: None,
+            health: MAX_HEALTH,
+            eggs: None,
         }
     }
 
@@ -113,6 +117,7 @@ impl<'a> Crop<'a> {
         self.rotten = r;
         if r {
             self.src.set_x(0);
+            self.health = 0.0;
         }
     }
 
@@ -120,9 +125,38 @@ impl<'a> Crop<'a> {
         self.rotten
     }
 
+    pub fn get_health(&self) -> f32 {
+        self.health
+    }
+
+    pub fn set_health(&mut self, h: f32) {
+        self.health = h.clamp(0.0, MAX_HEALTH);
+    }
+
+    /// Hide a pest egg in the seed, or take it out with None
+    pub fn set_eggs(&mut self, eggs: Option<(usize, Pest)>) {
+        self.eggs = eggs;
+    }
+
+    /// Take `amount` damage. Returns true if the crop has no health left.
+    pub fn damage(&mut self, amount: f32) -> bool {
+        self.set_health(self.health - amount);
+        self.health == 0.0
+    }
+
+    /// Watered crops recover a little health each night
+    pub fn heal(&mut self) {
+        if self.watered {
+            self.set_health(self.health + HEAL_RATE);
+        }
+    }
+
     /// Checks if a crop has been watered, then increments its
     /// stage of growth, clamping to `0..3`
-    pub fn grow(&mut self) {
+    ///
+    /// # Arguments
+    /// * `evaporation` - how quickly water dries up tonight; 1 is normal
+    pub fn grow(&mut self, evaporation: f32) {
         if self.get_watered() && self.stage != 3 {
             // Choose random value; if it is less than a crop's
             // growth rate, let it grow
@@ -136,7 +170,7 @@ impl<'a> Crop<'a> {
                     if let Some(mut w) = self.get_gene(genes::GeneType::WaterRetention) {
                         let mut rng = rand::thread_rng();
                         let watered_check: f32 = rng.gen();
-                        w = w / 2.0;
+                        w = w / (2.0 * evaporation);
                         if watered_check < w {
                             self.watered = true;
                         } else {
@@ -262,8 +296,12 @@ impl<'a> Crop<'a> {
         self.stage
     }
 
+    /// Set the stage of growth, moving `src` along the sprite sheet to match
     pub fn set_stage(&mut self, n: u8) {
         self.stage = n;
+        if self.t != CropType::None {
+            self.src.set_x(n as i32 * TILE_SIZE as i32);
+        }
     }
 
     pub fn set_pollinated(&mut self, p: bool) {
@@ -378,6 +416,7 @@ impl<'a> Crop<'a> {
             genes::GeneType::Value,
             genes::GeneType::WaterRetention,
             genes::GeneType::PestResistance,
+            genes::GeneType::DiseaseResistance,
         ];
 
         for t in types {
@@ -412,6 +451,14 @@ impl<'a> Crop<'a> {
         } else {
             s.push_str(String::from("None;").as_ref());
         }
+        s.push_str(&format!("{};", self.health));
+        if let Some((species, egg)) = &self.eggs {
+            s.push_str(&format!(
+                "eggs;{};{}",
+                species,
+                egg.to_save_string().trim_end()
+            ));
+        }
         // s.push_str(self.genes.as_ref().unwrap().to_save_string().as_ref());
         s.push('\n');
 
@@ -431,23 +478,38 @@ impl<'a> Crop<'a> {
     /// 8. value gene
     /// 9. water retention gene
     /// 10. pest resistance
+    /// 11. disease resistance
     /// CHILD
-    /// 11. child growth rate / "None" if no child
-    /// 12. child value
-    /// 13. child water retention
-    /// 14. child pest resistance
+    /// 12. child growth rate / "None" if no child
+    /// 13. child value
+    /// 14. child water retention
+    /// 15. child pest resistance
+    /// 16. child disease resistance
+    /// HEALTH
+    /// 17. health, or 13 if there is no child; full for older saves
+    /// EGGS
+    /// "eggs", then the species index and the egg, for seeds carrying one
+    ///
+    /// Crops saved before disease resistance have 4 genes each
     pub fn from_save_string(s: &Vec<&str>, t: &'a Texture<'a>, rt: &'a Texture<'a>) -> Crop<'a> {
         let g;
         // println!("Loading from {:?}, len = {:?}", s, s.len());
-        // TODO add to this as more genes are added or make from_save_string in Genes
+        let n = if s.get(7 + genes::NUM_GENES) == Some(&"None")
+            || (s[11] != "None" && s.len() >= 8 + 2 * genes::NUM_GENES)
+        {
+            genes::NUM_GENES
+        } else {
+            4
+        };
+        let parse_genes = |from: usize| -> Vec<f32> {
+            s[from..from + n]
+                .iter()
+                .map(|v| v.parse().unwrap())
+                .collect()
+        };
 
         if s.len() > 8 {
-            g = Some(genes::Genes::make_genes(vec![
-                s[7].parse::<f32>().unwrap(),
-                s[8].parse::<f32>().unwrap(),
-                s[9].parse::<f32>().unwrap(),
-                s[10].parse::<f32>().unwrap(),
-            ]));
+            g = Some(genes::Genes::make_genes(parse_genes(7)));
         } else {
             g = None;
         }
@@ -466,15 +528,24 @@ impl<'a> Crop<'a> {
             g,
         );
         c.set_pollinated(s[5].parse::<bool>().unwrap());
-        if s[11] == "None" {
+        let child_idx = 7 + n;
+        let health_idx = if s[child_idx] == "None" {
             c.set_child(None);
+            child_idx + 1
         } else {
-            c.set_child(Some(genes::Genes::make_genes(vec![
-                s[11].parse::<f32>().unwrap(),
-                s[12].parse::<f32>().unwrap(),
-                s[13].parse::<f32>().unwrap(),
-                s[14].parse::<f32>().unwrap(),
-            ])));
+            c.set_child(Some(genes::Genes::make_genes(parse_genes(child_idx))));
+            child_idx + n
+        };
+        c.set_health(
+            s.get(health_idx)
+                .and_then(|h| h.parse::<f32>().ok())
+                .unwrap_or(MAX_HEALTH),
+        );
+        if let Some(i) = s.iter().position(|v| *v == "eggs") {
+            c.set_eggs(Some((
+                s[i + 1].parse::<usize>().unwrap(),
+                Pest::from_save_string(s[i + 2..].to_vec()),
+            )));
         }
         c
     }
@@ -518,6 +589,10 @@ impl InventoryItemTrait for Crop<'_> {
             _c.set_stage(0);
             _c.set_water(false);
             _c.set_genes(self.get_all_genes().clone());
+            _c.set_health(MAX_HEALTH);
+            if let Some((species, egg)) = &self.eggs {
+                pop.set_colony(x as u32, y as u32, Some(Colony::new(*species, egg.clone())));
+            }
 
             // Return none for right now to signal a crop was placed
             return Some((Some(CropType::None), None, None));
@@ -542,18 +617,10 @@ impl InventoryItemTrait for Crop<'_> {
 
         Some(self.to_save_string())
     }
-}
-
-impl FromStr for CropType {
-    type Err = ();
 
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        match s {
-            "Carrot" => Ok(CropType::Carrot),
-            "Corn" => Ok(CropType::Corn),
-            "Lettuce" => Ok(CropType::Lettuce),
-            "Potato" => Ok(CropType::Potato),
-            _ => Err(()),
-        }
+    fn value_gene(&self) -> Option<f32> {
+        self.get_all_genes()
+            .as_ref()
+            .map(|g| g.get_gene(genes::GeneType::Value))
     }
 }
@@ -0,0 +1,59 @@
+//! Module for the kinds of crop, kept apart from the crops themselves
+//! so code that only needs to know what is planted doesn't need SDL
+
+use std::str::FromStr;
+
+use crate::calendar::Season;
+
+/// Crop type enum
+#[derive(Copy, Clone, PartialEq, Debug)]
+
+pub enum CropType {
+    None,
+    Carrot,
+    Corn,
+    Potato,
+    Lettuce,
+}
+
+impl CropType {
+    /// Seasons this species can grow in
+    pub fn seasons(&self) -> &'static [Season] {
+        match self {
+            CropType::None => &[],
+            CropType::Carrot => &[Season::Spring, Season::Fall],
+            CropType::Corn => &[Season::Summer],
+            CropType::Potato => &[Season::Spring, Season::Summer],
+            CropType::Lettuce => &[Season::Spring, Season::Fall],
+        }
+    }
+
+    pub fn grows_in(&self, season: Season) -> bool {
+        self.seasons().contains(&season)
+    }
+
+    /// Chance, from 0 to 1, that this species comes through a frost unharmed
+    pub fn frost_tolerance(&self) -> f32 {
+        match self {
+            CropType::None => 1.0,
+            CropType::Carrot => 0.7,
+            CropType::Corn => 0.2,
+            CropType::Potato => 0.5,
+            CropType::Lettuce => 0.6,
+        }
+    }
+}
+
+impl FromStr for CropType {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "Carrot" => Ok(CropType::Carrot),
+            "Corn" => Ok(CropType::Corn),
+            "Lettuce" => Ok(CropType::Lettuce),
+            "Potato" => Ok(CropType::Potato),
+            _ => Err(()),
+        }
+    }
+}
@@ -0,0 +1,274 @@
+//! Module for plant diseases. Unlike pests, diseases aren't carried
+//! by anything the player can see coming: they break out on their own
+//! and spread from crop to crop, fungi most of all in wet weather and
+//! viruses wherever crops are packed close together. Fungicide cures
+//! fungal infections; viral ones can only be stopped by pulling up
+//! the plants.
+
+use rand::Rng;
+use sdl2::image::LoadTexture;
+use sdl2::rect::Rect;
+use sdl2::render::{Texture, TextureCreator};
+use sdl2::video::WindowContext;
+use std::str::FromStr;
+
+use crate::crop::CropType;
+use crate::genes;
+use crate::population::Population;
+use crate::InventoryItemTrait;
+use crate::TILE_SIZE;
+
+/// Texture of the symptoms drawn over infected crops
+pub const SYMPTOMS_TEX_PATH: &str = "src/images/disease.png";
+/// Texture of fungicide in the inventory and the store
+pub const FUNGICIDE_TEX_PATH: &str = "src/images/fungicide.png";
+
+/// What causes a disease
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum Pathogen {
+    /// Spreads by spores, which need moisture. Cured by fungicide and
+    /// lives on in rotten plants.
+    Fungal,
+    /// Spreads by contact, so crowding matters most. Has no cure and
+    /// dies with its plant.
+    Viral,
+}
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum Disease {
+    Blight,
+    Mosaic,
+}
+
+impl Disease {
+    /// Every disease, for outbreaks
+    const ALL: [Disease; 2] = [Disease::Blight, Disease::Mosaic];
+
+    pub fn pathogen(&self) -> Pathogen {
+        match self {
+            Disease::Blight => Pathogen::Fungal,
+            Disease::Mosaic => Pathogen::Viral,
+        }
+    }
+
+    /// Health an infected crop loses each night
+    fn damage(&self) -> f32 {
+        match self {
+            Disease::Blight => 0.1,
+            Disease::Mosaic => 0.05,
+        }
+    }
+
+    /// Chance each night that the disease breaks out on a crop somewhere
+    /// on the farm
+    fn outbreak_chance(&self, wet: bool) -> f32 {
+        match (self.pathogen(), wet) {
+            (Pathogen::Fungal, true) => 0.1,
+            (Pathogen::Fungal, false) => 0.01,
+            (Pathogen::Viral, _) => 0.02,
+        }
+    }
+
+    /// Chance of spreading to a neighbor before its disease resistance,
+    /// given how damp its soil is and how crowded it is, both from 0 to 1
+    fn spread_chance(&self, moisture: f32, crowding: f32) -> f32 {
+        match self.pathogen() {
+            Pathogen::Fungal => 0.5 * moisture * (0.5 + crowding),
+            Pathogen::Viral => 0.3 * (0.25 + crowding),
+        }
+    }
+
+    /// Rectangle of `disease.png` with the disease's symptoms
+    pub fn src(&self) -> Rect {
+        let frame = match self {
+            Disease::Blight => 0,
+            Disease::Mosaic => 1,
+        };
+        Rect::new(frame * TILE_SIZE as i32, 0, TILE_SIZE, TILE_SIZE)
+    }
+}
+
+impl FromStr for Disease {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "Blight" => Ok(Disease::Blight),
+            "Mosaic" => Ok(Disease::Mosaic),
+            _ => Err(format!("invalid Disease: {}", s)),
+        }
+    }
+}
+
+impl std::fmt::Display for Disease {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Disease::Blight => write!(f, "Blight"),
+            Disease::Mosaic => write!(f, "Mosaic"),
+        }
+    }
+}
+
+/// Returns true if (x, y) holds a living crop a disease can infect
+fn is_host(pop: &Population, x: u32, y: u32) -> bool {
+    let c = pop.get_crop_with_index(x, y);
+    c.get_crop_type_enum() != CropType::None && !c.rotten()
+}
+
+/// The crop at (x, y) resists an infection with its disease resistance gene.
+/// Returns true if it was infected.
+fn try_infect(pop: &mut Population, x: u32, y: u32, d: Disease, chance: f32) -> bool {
+    let resistance = pop
+        .get_crop_with_index(x, y)
+        .get_gene(genes::GeneType::DiseaseResistance)
+        .unwrap_or(0.0);
+    if rand::thread_rng().gen::<f32>() < chance * (1.0 - resistance) {
+        pop.set_disease(x, y, Some(d));
+        return true;
+    }
+    false
+}
+
+/// Share of the 8 tiles around (x, y) with a crop on them
+fn crowding(pop: &Population, x: u32, y: u32) -> f32 {
+    let mut planted = 0;
+    for dx in -1..=1 {
+        for dy in -1..=1 {
+            let (nx, ny) = (x as i32 + dx, y as i32 + dy);
+            if (dx != 0 || dy != 0)
+                && pop.in_bounds(nx, ny)
+                && pop
+                    .get_crop_with_index(nx as u32, ny as u32)
+                    .get_crop_type_enum()
+                    != CropType::None
+            {
+                planted += 1;
+            }
+        }
+    }
+    planted as f32 / 8.0
+}
+
+/// How damp the crop at (x, y) is: soaked in the rain, damp if watered
+fn moisture(pop: &Population, x: u32, y: u32, raining: bool) -> f32 {
+    if raining {
+        1.0
+    } else if pop.get_crop_with_index(x, y).get_watered() {
+        0.6
+    } else {
+        0.2
+    }
+}
+
+/// Nightly step for diseases. Each infected crop loses health, rotting
+/// once it has none left, and tries to infect the crops next to it.
+/// Fungal infections live on in rotten plants until they are cleared;
+/// viral ones die with their plant. Then new outbreaks may start.
+pub fn night(pop: &mut Population, raining: bool) {
+    let mut rng = rand::thread_rng();
+    let mut spreads = Vec::new();
+
+    for x in 0..pop.width() {
+        for y in 0..pop.height() {
+            let d = match pop.get_disease(x, y) {
+                Some(d) => d,
+                None => continue,
+            };
+            if pop.get_crop_with_index(x, y).get_crop_type_enum() == CropType::None {
+                pop.set_disease(x, y, None);
+                continue;
+            }
+            if !pop.get_crop_with_index(x, y).rotten() {
+                let c = pop.get_crop_with_index_mut(x, y);
+                if c.damage(d.damage()) {
+                    c.set_rotten(true);
+                    c.set_stage(0);
+                    c.set_water(false);
+                    c.set_genes(None);
+                }
+            }
+            if pop.get_crop_with_index(x, y).rotten() && d.pathogen() == Pathogen::Viral {
+                pop.set_disease(x, y, None);
+                continue;
+            }
+            for (dx, dy) in [(-1, 0), (1, 0), (0, -1), (0, 1)] {
+                let (nx, ny) = (x as i32 + dx, y as i32 + dy);
+                if pop.in_bounds(nx, ny) {
+                    spreads.push((nx as u32, ny as u32, d));
+                }
+            }
+        }
+    }
+
+    // Spread after every infection has had its turn so new infections
+    // don't spread on the night they start
+    for (x, y, d) in spreads {
+        if is_host(pop, x, y) && pop.get_disease(x, y).is_none() {
+            let chance = d.spread_chance(moisture(pop, x, y, raining), crowding(pop, x, y));
+            try_infect(pop, x, y, d, chance);
+        }
+    }
+
+    // Outbreaks on a random crop
+    let mut hosts = Vec::new();
+    for x in 0..pop.width() {
+        for y in 0..pop.height() {
+            if is_host(pop, x, y) && pop.get_disease(x, y).is_none() {
+                hosts.push((x, y));
+            }
+        }
+    }
+    for d in Disease::ALL {
+        if hosts.is_empty() || rng.gen::<f32>() >= d.outbreak_chance(raining) {
+            continue;
+        }
+        let (x, y) = hosts[rng.gen_range(0..hosts.len())];
+        if try_infect(pop, x, y, d, 1.0) {
+            println!("{} has broken out on the farm", d);
+        }
+    }
+}
+
+/// A bottle of fungicide sitting in the player's inventory
+pub struct Fungicide<'a> {
+    texture: Texture<'a>,
+}
+
+impl<'a> Fungicide<'a> {
+    pub fn new(texture_creator: &'a TextureCreator<WindowContext>) -> Fungicide<'a> {
+        Fungicide {
+            texture: texture_creator.load_texture(FUNGICIDE_TEX_PATH).unwrap(),
+        }
+    }
+}
+
+impl InventoryItemTrait for Fungicide<'_> {
+    fn get_value(&self) -> i32 {
+        1
+    }
+    fn texture(&self) -> &Texture {
+        &self.texture
+    }
+    fn src(&self) -> Rect {
+        Rect::new(0, 0, TILE_SIZE, TILE_SIZE)
+    }
+    /// Cure a fungal infection. Returns the same value as planting a
+    /// seed so the bottle is used up.
+    fn inventory_input(
+        &self,
+        square: (i32, i32),
+        pop: &mut Population,
+    ) -> Option<(Option<CropType>, Option<genes::Genes>, Option<genes::Genes>)> {
+        let (x, y) = (square.0 as u32, square.1 as u32);
+        match pop.get_disease(x, y) {
+            Some(d) if d.pathogen() == Pathogen::Fungal => {
+                pop.set_disease(x, y, None);
+                Some((Some(CropType::None), None, None))
+            }
+            _ => None,
+        }
+    }
+    fn to_save_string(&self) -> Option<String> {
+        Some("fungicide\n".to_string())
+    }
+}
@@ -0,0 +1,306 @@
+//! Module for the economy of the valley around the farm. Farmers grow
+//! crops from seed, seed growers save seed from crops and households
+//! buy crops to eat, all trading in the market house. Each night they
+//! trade a few rounds, and the prices they settle on are what the
+//! market pays and charges.
+
+use crate::agent::EconAgent;
+use crate::commodities::{Commodities, CommodityKind};
+use crate::market_house::{ClearingReport, MarketHouse};
+
+/// Rounds of trading the valley does each night
+const ROUNDS_PER_NIGHT: usize = 3;
+/// Rounds traded before the game starts so prices have settled
+const WARM_UP_ROUNDS: usize = 20;
+/// Rounds of history used when looking at prices and demand
+const HISTORY: i32 = 10;
+
+/// Producers of each crop and of each seed when the valley starts out
+const FARMERS_PER_CROP: usize = 4;
+const SEED_GROWERS_PER_CROP: usize = 2;
+const HOUSEHOLDS: usize = 8;
+
+/// Cash every new agent starts with
+const START_CASH: f32 = 100.0;
+/// Inputs a new producer starts with
+const START_STOCK: f32 = 5.0;
+/// Most of each commodity an agent keeps
+const MAX_STOCK: f32 = 20.0;
+/// Crops of each kind a household keeps in its pantry
+const PANTRY_SIZE: f32 = 4.0;
+/// Crops of each kind a household eats every round
+const HOUSEHOLD_APPETITE: f32 = 1.0;
+/// Cash a household earns every round
+const HOUSEHOLD_WAGE: f32 = 35.0;
+/// Most of each commodity the market stall holds for sale
+const STALL_CAPACITY: f32 = 1000.0;
+
+/// What an agent does in the economy
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum Role {
+    /// Makes the commodity from its recipe. Farmers make crops and seed
+    /// growers make seeds.
+    Producer(CommodityKind),
+    /// Buys crops and eats them
+    Household,
+    /// The market stall, which sells on what the player sells it
+    Stall,
+}
+
+/// Commodities households eat
+const CROPS: [CommodityKind; 4] = [
+    CommodityKind::CarrotCrop,
+    CommodityKind::CornCrop,
+    CommodityKind::PotatoCrop,
+    CommodityKind::LettuceCrop,
+];
+
+fn is_crop(c: CommodityKind) -> bool {
+    CROPS.contains(&c)
+}
+
+pub struct Economy {
+    commodities: Commodities,
+    agents: Vec<EconAgent>,
+    roles: Vec<Role>,
+    market: MarketHouse,
+    /// How each commodity's orders went in the latest round
+    reports: Vec<ClearingReport>,
+}
+
+impl Economy {
+    pub fn new() -> Economy {
+        let mut commodities = Commodities::new();
+        commodities.init();
+        let mut economy = Economy {
+            commodities,
+            agents: Vec::new(),
+            roles: Vec::new(),
+            market: MarketHouse::new(),
+            reports: Vec::new(),
+        };
+        for c in CommodityKind::ALL {
+            let count = if is_crop(c) {
+                FARMERS_PER_CROP
+            } else {
+                SEED_GROWERS_PER_CROP
+            };
+            for _ in 0..count {
+                economy.add_agent(Role::Producer(c));
+            }
+        }
+        for _ in 0..HOUSEHOLDS {
+            economy.add_agent(Role::Household);
+        }
+        economy.add_agent(Role::Stall);
+        for _ in 0..WARM_UP_ROUNDS {
+            economy.round();
+        }
+        economy
+    }
+
+    fn new_agent(&mut self, role: Role) -> EconAgent {
+        let mut agent = EconAgent::new();
+        match role {
+            Role::Producer(c) => agent.init_agent(
+                START_CASH,
+                &vec![c],
+                START_STOCK,
+                MAX_STOCK,
+                &mut self.commodities,
+            ),
+            Role::Household => {
+                agent.init_agent(
+                    START_CASH,
+                    &Vec::new(),
+                    0.0,
+                    PANTRY_SIZE,
+                    &mut self.commodities,
+                );
+                for c in CROPS {
+                    let price = self.commodities.get_mut(c).unwrap().get_price();
+                    agent.add_to_stock_pile(c, 0.0, PANTRY_SIZE, price, 1.0);
+                }
+            }
+            Role::Stall => {
+                agent.init_agent(0.0, &Vec::new(), 0.0, STALL_CAPACITY, &mut self.commodities);
+                for c in CommodityKind::ALL {
+                    let price = self.commodities.get_mut(c).unwrap().get_price();
+                    agent.add_to_stock_pile(c, 0.0, STALL_CAPACITY, price, 1.0);
+                }
+            }
+        }
+        agent
+    }
+
+    fn add_agent(&mut self, role: Role) {
+        let agent = self.new_agent(role);
+        self.agents.push(agent);
+        self.roles.push(role);
+    }
+
+    /// The commodity buyers have wanted most more than sellers have had it
+    fn most_demanded(&mut self) -> CommodityKind {
+        let mut best = CommodityKind::ALL[0];
+        let mut best_ratio = f32::MIN;
+        for c in CommodityKind::ALL {
+            let ratio = self.commodities.get_mut(c).unwrap().demand_ratio(HISTORY);
+            if ratio > best_ratio {
+                best = c;
+                best_ratio = ratio;
+            }
+        }
+        best
+    }
+
+    /// Produce, bid, trade and eat once. Bankrupt producers are replaced
+    /// by producers of whatever is most in demand.
+    fn round(&mut self) {
+        for (i, agent) in self.agents.iter_mut().enumerate() {
+            if self.roles[i] == Role::Stall {
+                self.market.submit_asks(agent.offer_stock(i));
+                continue;
+            }
+            let (_idle_tax, asks) = agent.produce_com_hash(i, &mut self.commodities);
+            self.market.submit_asks(asks);
+            let bids = agent.consume_com_hash(i, &mut self.commodities);
+            self.market.submit_bids(bids);
+        }
+        self.reports = self
+            .market
+            .resolve_all(&mut self.agents, &mut self.commodities);
+
+        for (agent, role) in self.agents.iter_mut().zip(&self.roles) {
+            if *role == Role::Household {
+                for c in CROPS {
+                    agent.use_up(c, HOUSEHOLD_APPETITE);
+                }
+                agent.add_cash(HOUSEHOLD_WAGE);
+            }
+        }
+
+        for i in 0..self.agents.len() {
+            if !self.agents[i].is_bankrupt() {
+                continue;
+            }
+            let role = match self.roles[i] {
+                Role::Household => Role::Household,
+                Role::Stall => Role::Stall,
+                Role::Producer(_) => Role::Producer(self.most_demanded()),
+            };
+            self.agents[i] = self.new_agent(role);
+            self.roles[i] = role;
+        }
+    }
+
+    /// Trade through the night
+    pub fn night(&mut self) {
+        for _ in 0..ROUNDS_PER_NIGHT {
+            self.round();
+        }
+    }
+
+    /// Average price `c` has traded at lately
+    pub fn get_price(&self, c: CommodityKind) -> f32 {
+        self.commodities.get(c).unwrap().get_avg_price(HISTORY)
+    }
+
+    /// Put `quantity` of `c` the player sold up for sale at the market
+    /// stall, adding to the supply the valley trades tonight
+    pub fn supply(&mut self, c: CommodityKind, quantity: f32) {
+        if let Some(i) = self.roles.iter().position(|r| *r == Role::Stall) {
+            self.agents[i].add_stock(c, quantity);
+        }
+    }
+
+    /// Save string of every commodity's recent prices, one
+    /// `prices;<commodity>;<price>;...` line each, and a
+    /// `stall;<quantity>;...` line of what the market stall holds of
+    /// each commodity, in the order of `CommodityKind::ALL`
+    pub fn to_save_string(&self) -> String {
+        let mut s = String::new();
+        for c in CommodityKind::ALL {
+            s.push_str(&format!("prices;{}", c.name()));
+            for p in self.commodities.get(c).unwrap().recent_prices(HISTORY) {
+                s.push_str(&format!(";{}", p));
+            }
+            s.push('\n');
+        }
+        if let Some(i) = self.roles.iter().position(|r| *r == Role::Stall) {
+            s.push_str("stall");
+            for c in CommodityKind::ALL {
+                s.push_str(&format!(";{}", self.agents[i].get_quantity(c)));
+            }
+            s.push('\n');
+        }
+        s
+    }
+
+    /// Put back what a line from `to_save_string`, already split on `;`,
+    /// says. Returns false for any other line.
+    pub fn load_save_string(&mut self, v: &[&str]) -> bool {
+        match v[0] {
+            "prices" => {
+                let c = match CommodityKind::ALL.iter().find(|c| c.name() == v[1]) {
+                    Some(c) => *c,
+                    None => return false,
+                };
+                let prices: Vec<f32> = v[2..].iter().map(|p| p.parse().unwrap()).collect();
+                self.commodities.get_mut(c).unwrap().set_prices(&prices);
+            }
+            "stall" => {
+                for (c, q) in CommodityKind::ALL.iter().zip(&v[1..]) {
+                    self.supply(*c, q.parse().unwrap());
+                }
+            }
+            _ => return false,
+        }
+        true
+    }
+
+    /// How the orders for `c` went in the latest round of trading
+    pub fn get_report(&self, c: CommodityKind) -> Option<ClearingReport> {
+        self.reports.iter().find(|r| r.commodity == c).copied()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bankrupt_agents_are_replaced() {
+        let mut economy = Economy::new();
+        let household = economy
+            .roles
+            .iter()
+            .position(|r| *r == Role::Household)
+            .unwrap();
+        economy.agents[0].add_cash(-1000.0);
+        economy.agents[household].add_cash(-1000.0);
+        economy.round();
+
+        // A bankrupt producer starts over making what is most wanted
+        assert_eq!(economy.agents[0].get_cash(), START_CASH);
+        let wanted = economy.most_demanded();
+        assert_eq!(economy.roles[0], Role::Producer(wanted));
+        assert_eq!(economy.roles[household], Role::Household);
+        assert!(economy.agents[household].get_cash() >= 0.0);
+    }
+
+    #[test]
+    fn save_string_round_trips() {
+        let mut economy = Economy::new();
+        economy.supply(CommodityKind::CornCrop, 3.0);
+        let s = economy.to_save_string();
+        let mut loaded = Economy::new();
+        for line in s.lines() {
+            assert!(loaded.load_save_string(&line.split(';').collect::<Vec<&str>>()));
+        }
+        assert_eq!(loaded.to_save_string(), s);
+        assert_eq!(
+            loaded.get_price(CommodityKind::CornCrop),
+            economy.get_price(CommodityKind::CornCrop)
+        );
+    }
+}
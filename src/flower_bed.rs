@@ -0,0 +1,66 @@
+//! Module for flower beds: placeable items that feed and shelter the
+//! ladybugs that hunt the farm's pests
+
+use sdl2::image::LoadTexture;
+use sdl2::rect::Rect;
+use sdl2::render::{Texture, TextureCreator};
+use sdl2::video::WindowContext;
+
+use crate::crop::CropType;
+use crate::genes;
+use crate::item::Item;
+use crate::population::Population;
+use crate::InventoryItemTrait;
+use crate::TILE_SIZE;
+
+/// Path of the texture used both on the map and in the inventory
+pub const TEX_PATH: &str = "src/images/flower_bed.png";
+
+/// A flower bed sitting in the player's inventory
+pub struct FlowerBed<'a> {
+    texture: Texture<'a>,
+}
+
+impl<'a> FlowerBed<'a> {
+    pub fn new(texture_creator: &'a TextureCreator<WindowContext>) -> FlowerBed<'a> {
+        FlowerBed {
+            texture: texture_creator.load_texture(TEX_PATH).unwrap(),
+        }
+    }
+}
+
+impl InventoryItemTrait for FlowerBed<'_> {
+    fn get_value(&self) -> i32 {
+        1
+    }
+    fn texture(&self) -> &Texture {
+        &self.texture
+    }
+    fn src(&self) -> Rect {
+        Rect::new(0, 0, TILE_SIZE, TILE_SIZE)
+    }
+    /// Placing a flower bed needs the area's item list, so it is
+    /// handled in the game loop instead
+    fn inventory_input(
+        &self,
+        _square: (i32, i32),
+        _pop: &mut Population,
+    ) -> Option<(Option<CropType>, Option<genes::Genes>, Option<genes::Genes>)> {
+        None
+    }
+    fn to_save_string(&self) -> Option<String> {
+        Some("flower_bed\n".to_string())
+    }
+    fn placed_tex_path(&self) -> Option<&'static str> {
+        Some(TEX_PATH)
+    }
+}
+
+/// Tile index of every flower bed in `item_vec`
+pub fn find_all(item_vec: &[Item]) -> Vec<(i32, i32)> {
+    item_vec
+        .iter()
+        .filter(|i| i.tex_path() == TEX_PATH)
+        .map(|i| (i.x() / TILE_SIZE as i32, i.y() / TILE_SIZE as i32))
+        .collect()
+}
@@ -5,6 +5,8 @@ use rand_distr::{Distribution, Normal};
 const MEAN: f32 = 0.5;
 // TODO adjust this value as needed to balance gene generation
 const STD_DEV: f32 = 0.1;
+/// Number of genes a crop has
+pub const NUM_GENES: usize = 5;
 
 /// Gene type enum
 #[derive(Copy, Clone, Debug)]
@@ -13,6 +15,7 @@ pub enum GeneType {
     Value,
     WaterRetention,
     PestResistance,
+    DiseaseResistance,
 }
 
 #[derive(Debug)]
@@ -35,6 +38,7 @@ impl Genes {
         let growth_var = normal.sample(&mut rand::thread_rng()).clamp(0.0, 1.0);
         let water_ret_var = normal.sample(&mut rand::thread_rng()).clamp(0.0, 1.0);
         let pest_resist_var = normal.sample(&mut rand::thread_rng()).clamp(0.0, 1.0);
+        let disease_resist_var = normal.sample(&mut rand::thread_rng()).clamp(0.0, 1.0);
         let value_var = (growth_var + water_ret_var + pest_resist_var) / 3.0;
         Genes {
             genes: vec![
@@ -42,17 +46,27 @@ impl Genes {
                 Gene::new(GeneType::Value, value_var),
                 Gene::new(GeneType::WaterRetention, water_ret_var),
                 Gene::new(GeneType::PestResistance, pest_resist_var),
+                Gene::new(GeneType::DiseaseResistance, disease_resist_var),
             ],
         }
     }
 
+    /// Make genes from their values, in `GeneType` order. Genes saved
+    /// before disease resistance existed get a random one.
     pub fn make_genes(v: Vec<f32>) -> Genes {
+        let disease_resist_var = v.get(4).copied().unwrap_or_else(|| {
+            Normal::new(MEAN, STD_DEV)
+                .unwrap()
+                .sample(&mut rand::thread_rng())
+                .clamp(0.0, 1.0)
+        });
         Genes {
             genes: vec![
                 Gene::new(GeneType::GrowthRate, *v.get(0).unwrap()),
                 Gene::new(GeneType::Value, *v.get(1).unwrap()),
                 Gene::new(GeneType::WaterRetention, *v.get(2).unwrap()),
                 Gene::new(GeneType::PestResistance, *v.get(3).unwrap()),
+                Gene::new(GeneType::DiseaseResistance, disease_resist_var),
             ],
         }
     }
@@ -64,9 +78,18 @@ impl Genes {
             GeneType::Value => self.genes.get(1).unwrap().value,
             GeneType::WaterRetention => self.genes.get(2).unwrap().value,
             GeneType::PestResistance => self.genes.get(3).unwrap().value,
+            GeneType::DiseaseResistance => self.genes.get(4).unwrap().value,
         }
     }
 
+    /// Copy of these genes for a harvest from a crop with the given
+    /// health; damage lowers the harvest's value
+    pub fn with_quality(&self, health: f32) -> Genes {
+        let mut g = self.clone();
+        g.genes[1].value *= health.clamp(0.0, 1.0);
+        g
+    }
+
     pub fn average(&self) -> f32 {
         let mut sum = 0.0;
         let mut count = 0;
@@ -106,6 +129,7 @@ impl std::fmt::Display for GeneType {
             GeneType::Value => write!(f, "Value"),
             GeneType::WaterRetention => write!(f, "WaterRetention"),
             GeneType::PestResistance => write!(f, "PestResistance"),
+            GeneType::DiseaseResistance => write!(f, "DiseaseResistance"),
         }
     }
 }
@@ -138,6 +162,10 @@ impl std::clone::Clone for Genes {
                     GeneType::PestResistance,
                     self.get_gene(GeneType::PestResistance),
                 ),
+                Gene::new(
+                    GeneType::DiseaseResistance,
+                    self.get_gene(GeneType::DiseaseResistance),
+                ),
             ],
         }
     }
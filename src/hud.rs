@@ -0,0 +1,86 @@
+//! Module for drawing text on the heads-up display
+//! using the `hud_font.png` sprite sheet
+
+use sdl2::pixels::Color;
+use sdl2::rect::Rect;
+use sdl2::render::{Texture, WindowCanvas};
+
+/// Sprite sheet of the HUD font, to be loaded once and passed to the
+/// drawing functions
+pub const FONT_TEX_PATH: &str = "src/images/hud_font.png";
+
+/// Characters in `hud_font.png`, in order
+const GLYPHS: &str = "ABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789:-/$%. ";
+/// Width of a glyph on the sprite sheet, including its outline
+const GLYPH_W: u32 = 5;
+/// Height of a glyph on the sprite sheet, including its outline
+const GLYPH_H: u32 = 7;
+
+/// Width in pixels of `text` drawn at `scale`
+pub fn text_width(text: &str, scale: u32) -> u32 {
+    text.chars().count() as u32 * (GLYPH_W - 1) * scale + scale
+}
+
+/// Height in pixels of a line of text drawn at `scale`
+pub fn text_height(scale: u32) -> u32 {
+    GLYPH_H * scale
+}
+
+/// Draw `text` in `font` with its top left corner at (x, y). Lowercase
+/// letters are drawn as uppercase and characters missing from the font
+/// are skipped.
+pub fn draw_text(
+    wincan: &mut WindowCanvas,
+    font: &Texture,
+    text: &str,
+    x: i32,
+    y: i32,
+    scale: u32,
+) {
+    let mut cur_x = x;
+    for c in text.to_uppercase().chars() {
+        if let Some(i) = GLYPHS.find(c) {
+            wincan
+                .copy(
+                    font,
+                    Rect::new((i as u32 * GLYPH_W) as i32, 0, GLYPH_W, GLYPH_H),
+                    Rect::new(cur_x, y, GLYPH_W * scale, GLYPH_H * scale),
+                )
+                .unwrap();
+        }
+        // Glyph outlines overlap by a pixel so text isn't too spread out
+        cur_x += ((GLYPH_W - 1) * scale) as i32;
+    }
+}
+
+/// Draw lines of text on a translucent panel with its top left corner at (x, y)
+pub fn draw_panel(
+    wincan: &mut WindowCanvas,
+    font: &Texture,
+    lines: &[String],
+    x: i32,
+    y: i32,
+    scale: u32,
+) {
+    let padding = 2 * scale;
+    let width = lines
+        .iter()
+        .map(|l| text_width(l, scale))
+        .max()
+        .unwrap_or(0);
+    let height = lines.len() as u32 * (text_height(scale) + scale);
+    wincan.set_draw_color(Color::RGBA(0, 0, 0, 120));
+    wincan
+        .fill_rect(Rect::new(x, y, width + 2 * padding, height + 2 * padding))
+        .unwrap();
+    for (i, line) in lines.iter().enumerate() {
+        draw_text(
+            wincan,
+            font,
+            line,
+            x + padding as i32,
+            y + padding as i32 + (i as u32 * (text_height(scale) + scale)) as i32,
+            scale,
+        );
+    }
+}
@@ -0,0 +1,128 @@
+//! Module for the pests living in the country around the farm. Each
+//! species has a regional pool of its own whose genes drift slowly
+//! whatever happens on the farm. A few of its pests wander onto the farm
+//! on nights in season, bringing back genes the farm's population may
+//! have bred out, and seeds bought at the market sometimes carry its
+//! eggs in with them.
+
+use rand::Rng;
+use rand_distr::{Distribution, Normal};
+
+use crate::pest;
+use crate::pest::{Pest, Stage};
+use crate::pest_population::PestPopulation;
+use crate::rng;
+
+/// How far each gene of a regional pool wanders in a night
+const POOL_DRIFT: f32 = 0.005;
+/// Share of the way back to a brand new pest's genes each gene of a
+/// pool is pulled every night, so pools never wander off for good
+const POOL_PULL: f32 = 0.001;
+/// Chance each night in season that pests wander onto the farm
+pub const IMMIGRATION_CHANCE: f32 = 0.3;
+/// Most pests that wander onto the farm in one night
+pub const MAX_IMMIGRANTS: usize = 5;
+/// Chance that a seed bought at the market carries a pest egg
+const SEED_EGG_CHANCE: f32 = 0.05;
+
+/// Genes of a species' pests outside the farm
+pub struct RegionalPool {
+    /// Average of each gene, in the order `Pest::make_pest` takes them
+    means: [f32; pest::NUM_GENES],
+}
+
+impl RegionalPool {
+    pub fn new() -> RegionalPool {
+        RegionalPool {
+            means: pest::BASE_MEANS,
+        }
+    }
+
+    pub fn get_means(&self) -> &[f32; pest::NUM_GENES] {
+        &self.means
+    }
+
+    /*
+       Every gene of the pool takes a small random step, pulled gently
+       back towards where it started
+    */
+    pub fn drift(&mut self) {
+        let normal = Normal::new(0.0, POOL_DRIFT).unwrap();
+        let mut rng = rng::rng();
+        for (m, base) in self.means.iter_mut().zip(pest::BASE_MEANS) {
+            *m = (*m + (base - *m) * POOL_PULL + normal.sample(&mut rng)).clamp(0.0, 1.0);
+        }
+    }
+
+    /// A new adult pest from the pool
+    pub fn immigrant(&self) -> Pest {
+        Pest::from_means(&self.means)
+    }
+
+    pub fn to_save_string(&self) -> String {
+        let mut s = String::from("pool");
+        for m in &self.means {
+            s.push_str(&format!(";{}", m));
+        }
+        s.push('\n');
+        s
+    }
+
+    /*
+       Reads a `pool;<mean>;...` line. Returns None for any other line
+    */
+    pub fn from_save_string(v: &[&str]) -> Option<RegionalPool> {
+        if v.len() < pest::NUM_GENES + 1 || v[0] != "pool" {
+            return None;
+        }
+        let mut means = [0.0; pest::NUM_GENES];
+        for (m, s) in means.iter_mut().zip(&v[1..]) {
+            *m = s.parse::<f32>().ok()?;
+        }
+        Some(RegionalPool { means })
+    }
+}
+
+/// Rolls whether a seed bought at the market carries a pest egg. Returns
+/// the index of the egg's species' population and the egg.
+pub fn seed_eggs(pest_pops: &[PestPopulation]) -> Option<(usize, Pest)> {
+    let mut rng = rng::rng();
+    if pest_pops.is_empty() || rng.gen::<f32>() >= SEED_EGG_CHANCE {
+        return None;
+    }
+    let species = rng.gen_range(0..pest_pops.len());
+    let egg = pest_pops[species]
+        .get_pool()
+        .immigrant()
+        .with_stage(Stage::Egg);
+    Some((species, egg))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn drift_stays_in_range() {
+        let mut pool = RegionalPool::new();
+        for _ in 0..10_000 {
+            pool.drift();
+        }
+        assert!(pool.get_means().iter().all(|m| (0.0..=1.0).contains(m)));
+    }
+
+    #[test]
+    fn save_string_round_trips() {
+        let mut pool = RegionalPool::new();
+        pool.drift();
+        let s = pool.to_save_string();
+        let v: Vec<&str> = s.trim_end().split(';').collect();
+        let loaded = RegionalPool::from_save_string(&v).unwrap();
+        assert_eq!(loaded.get_means(), pool.get_means());
+    }
+
+    #[test]
+    fn other_lines_are_not_pools() {
+        assert!(RegionalPool::from_save_string(&["history", "0.1"]).is_none());
+    }
+}
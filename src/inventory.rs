@@ -4,8 +4,13 @@ use sdl2::render::WindowCanvas;
 
 use crate::crop::Crop;
 use crate::crop::CropType;
+use crate::disease::Fungicide;
+use crate::flower_bed::FlowerBed;
 use crate::genes;
+use crate::pesticide::Pesticide;
 use crate::population::Population;
+use crate::protection::{Protection, ProtectionKind};
+use crate::sprinkler::Sprinkler;
 use crate::tool::Tool;
 use crate::InventoryItemTrait;
 
@@ -15,7 +20,7 @@ use sdl2::video::WindowContext;
 
 // use sdl2::render::TextureQuery;
 
-static INVENTORY_X_POS: i32 = 261;
+static INVENTORY_X_POS: i32 = 28;
 static INVENTORY_Y_POS: i32 = 640;
 
 static ITEM_BOX_SIZE: i32 = 64;
@@ -23,6 +28,22 @@ static BORDER_SIZE: i32 = 4;
 static SELECTED_SIZE: i32 = 2;
 static NUMBER_SIZE: i32 = 20;
 
+/// Number of inventory slots: 3 tools, 8 crop/seed slots, sprinklers,
+/// pesticide, flower beds, the three kinds of protection and fungicide
+pub const INVENTORY_SLOTS: i32 = 18;
+/// Slot holding sprinklers waiting to be placed
+pub const SPRINKLER_SLOT: i32 = 11;
+/// Slot holding cans of pesticide
+pub const PESTICIDE_SLOT: i32 = 12;
+/// Slot holding flower beds waiting to be planted
+pub const FLOWER_SLOT: i32 = 13;
+/// Slots holding scarecrows, sticky traps and row covers
+pub const SCARECROW_SLOT: i32 = 14;
+pub const TRAP_SLOT: i32 = 15;
+pub const COVER_SLOT: i32 = 16;
+/// Slot holding bottles of fungicide
+pub const FUNGICIDE_SLOT: i32 = 17;
+
 /// Individual inventory slot. This takes in an inventory trait object(crop or tool)
 /// Inventory slots are sorted, so you have the "best" seed at the bottom of the queue
 /// This is done so that seed can have different genetics, but still have one inventory slot
@@ -88,8 +109,9 @@ pub struct Inventory<'a> {
 impl<'a> Inventory<'a> {
     pub fn new(texture_creator: &'a TextureCreator<WindowContext>) -> Inventory<'a> {
         // Initializes inventory slots and sets tool slots to true
-        let mut inventory_slots: Vec<InventoryItem> =
-            (0..11).map(|x| InventoryItem::new(x < 3)).collect();
+        let mut inventory_slots: Vec<InventoryItem> = (0..INVENTORY_SLOTS)
+            .map(|x| InventoryItem::new(x < 3))
+            .collect();
 
         // Add tool slots into the inventory
         inventory_slots[0].add_item(Box::new(Tool::new(
@@ -119,7 +141,7 @@ impl<'a> Inventory<'a> {
         let temp_select = 0;
 
         // Initialize squares to be drawn
-        let squares: Vec<Rect> = (0..11)
+        let squares: Vec<Rect> = (0..INVENTORY_SLOTS)
             .map(|x| {
                 Rect::new(
                     INVENTORY_X_POS + (x * (ITEM_BOX_SIZE + BORDER_SIZE)),
@@ -146,7 +168,7 @@ impl<'a> Inventory<'a> {
             .fill_rect(Rect::new(
                 INVENTORY_X_POS - BORDER_SIZE,
                 INVENTORY_Y_POS - BORDER_SIZE,
-                (11 * (ITEM_BOX_SIZE + BORDER_SIZE) + BORDER_SIZE) as u32,
+                (INVENTORY_SLOTS * (ITEM_BOX_SIZE + BORDER_SIZE) + BORDER_SIZE) as u32,
                 (ITEM_BOX_SIZE + 2 * BORDER_SIZE) as u32,
             ))
             .expect("ERROR");
@@ -269,6 +291,46 @@ impl<'a> Inventory<'a> {
         self.inventory_slots[k].add_item(Box::new(new_crop));
     }
 
+    /// Add a sprinkler into the sprinkler slot
+    pub fn add_sprinkler(&mut self, new_sprinkler: Sprinkler<'a>) {
+        self.inventory_slots[SPRINKLER_SLOT as usize].add_item(Box::new(new_sprinkler));
+    }
+
+    /// Add a can of pesticide into the pesticide slot
+    pub fn add_pesticide(&mut self, new_pesticide: Pesticide<'a>) {
+        self.inventory_slots[PESTICIDE_SLOT as usize].add_item(Box::new(new_pesticide));
+    }
+
+    /// Add a bottle of fungicide into the fungicide slot
+    pub fn add_fungicide(&mut self, new_fungicide: Fungicide<'a>) {
+        self.inventory_slots[FUNGICIDE_SLOT as usize].add_item(Box::new(new_fungicide));
+    }
+
+    /// Add a flower bed into the flower bed slot
+    pub fn add_flower_bed(&mut self, new_flower_bed: FlowerBed<'a>) {
+        self.inventory_slots[FLOWER_SLOT as usize].add_item(Box::new(new_flower_bed));
+    }
+
+    /// Add a protection into the slot for its kind
+    pub fn add_protection(&mut self, kind: ProtectionKind, new_protection: Protection<'a>) {
+        let slot = match kind {
+            ProtectionKind::Scarecrow => SCARECROW_SLOT,
+            ProtectionKind::StickyTrap => TRAP_SLOT,
+            ProtectionKind::RowCover => COVER_SLOT,
+        };
+        self.inventory_slots[slot as usize].add_item(Box::new(new_protection));
+    }
+
+    /// If the selected slot holds something that can be placed on the
+    /// map, such as a sprinkler, remove the best one and return the
+    /// texture path of the item to place
+    pub fn take_placeable(&mut self) -> Option<&'static str> {
+        let slot = &mut self.inventory_slots[self.selected as usize];
+        let tex_path = slot.get_item(0)?.placed_tex_path()?;
+        slot.pop_item();
+        Some(tex_path)
+    }
+
     pub fn get_inventory_slot(&self, index: i32) -> Option<&InventoryItem> {
         self.inventory_slots.get(index as usize)
     }
@@ -314,6 +376,45 @@ impl<'a> Inventory<'a> {
         }
     }
 
+    /// Type, whether they're seeds and size of every crop and seed stack
+    /// that isn't empty, in inventory order
+    pub fn crop_stacks(&self) -> Vec<(CropType, bool, i32)> {
+        let kinds = [
+            CropType::Carrot,
+            CropType::Corn,
+            CropType::Potato,
+            CropType::Lettuce,
+        ];
+        let mut stacks = Vec::new();
+        for kind in kinds {
+            for seedy in [false, true] {
+                let len = self.inventory_slots[Inventory::crop_idx(kind, seedy)].get_len();
+                if len > 0 {
+                    stacks.push((kind, seedy, len));
+                }
+            }
+        }
+        stacks
+    }
+
+    /// Value gene of the best crop, or seed if `seedy`, of type `kind`
+    pub fn best_crop_value(&self, kind: CropType, seedy: bool) -> Option<f32> {
+        self.inventory_slots[Inventory::crop_idx(kind, seedy)]
+            .get_item(0)
+            .map(|item| item.value_gene().unwrap_or(0.0))
+    }
+
+    /// Remove the best crop, or seed if `seedy`, of type `kind`.
+    /// Returns false if there are none.
+    pub fn take_crop(&mut self, kind: CropType, seedy: bool) -> bool {
+        let slot = &mut self.inventory_slots[Inventory::crop_idx(kind, seedy)];
+        if slot.get_len() == 0 {
+            return false;
+        }
+        slot.pop_item();
+        true
+    }
+
     /// Eat a food yum. Or no food!
     pub fn eat(&mut self, kind: CropType) -> bool {
         let k = Inventory::crop_idx(kind, false);
@@ -1,5 +1,9 @@
+use sdl2::image::LoadTexture;
 use sdl2::rect::Rect;
-use sdl2::render::{Texture, WindowCanvas};
+use sdl2::render::{Texture, TextureCreator, WindowCanvas};
+use sdl2::video::WindowContext;
+
+use crate::TILE_SIZE;
 
 pub struct Item<'a> {
     pos: Rect,
@@ -18,6 +22,26 @@ impl<'a> Item<'a> {
         }
     }
 
+    /// A sprinkler, flower bed or protection put down on tile index
+    /// (x, y), drawn with the texture at `tex_path`
+    pub fn placed(
+        tex_path: &str,
+        square: (i32, i32),
+        texture_creator: &'a TextureCreator<WindowContext>,
+    ) -> Item<'a> {
+        Item::new(
+            Rect::new(
+                square.0 * TILE_SIZE as i32,
+                square.1 * TILE_SIZE as i32,
+                TILE_SIZE,
+                TILE_SIZE,
+            ),
+            texture_creator.load_texture(tex_path).unwrap(),
+            tex_path.to_string(),
+            false,
+        )
+    }
+
     pub fn x(&self) -> i32 {
         self.pos.x()
     }
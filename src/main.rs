@@ -5,9 +5,19 @@ extern crate sdl2;
 // Modules
 mod agent;
 mod anim;
+mod calendar;
+mod clock;
+mod colony;
 mod commodities;
+mod config;
 mod crop;
+mod crop_type;
+mod disease;
+mod economy;
+mod flower_bed;
 mod genes;
+mod hud;
+mod immigration;
 mod inventory;
 mod item;
 mod market;
@@ -15,13 +25,25 @@ mod market_house;
 mod market_item;
 mod pest;
 mod pest_population;
+mod pest_species;
+mod pest_stats;
+mod pesticide;
 mod player;
 mod population;
+mod predator;
+mod protection;
+mod rng;
 mod save_load;
+mod selection;
+mod sell;
 mod sleep_menu;
+mod sprinkler;
+mod stats_screen;
 mod store;
+mod terrain;
 mod tile;
 mod tool;
+mod weather;
 
 use anim::Animation;
 
@@ -35,7 +57,7 @@ use sdl2::render::BlendMode;
 use sdl2::render::Texture;
 use std::collections::HashSet;
 use std::thread;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use crate::crop::CropType;
 use crate::market_item::MarketItem;
@@ -45,24 +67,21 @@ const VSYNC: bool = true;
 // Camera dimensions
 pub const CAM_W: u32 = 1280;
 pub const CAM_H: u32 = 720;
-// Background dimensions
-const BG_W: u32 = 3000;
-const BG_H: u32 = 3000;
+/// How long the "store closed" notice stays up
+const NOTICE_TIME: Duration = Duration::from_secs(2);
 const TITLE: &str = "Farnan's Farmers";
 pub const TILE_SIZE: u32 = 80; // Make this public so we can import it elsewhere
 
-// Right and bottom tile bounds
-pub const RIGHT_TILE_BOUND: i32 = (BG_W / TILE_SIZE) as i32;
-pub const BOTTOM_TILE_BOUND: i32 = (BG_H / TILE_SIZE) as i32;
-
 pub enum Menu {
     Sleep,
     ToMarket,
     ToHome,
     Shop,
+    Sell,
+    PestStats,
 }
 
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, PartialEq)]
 pub enum Area {
     Home,
     Market,
@@ -88,6 +107,16 @@ pub trait InventoryItemTrait {
     )>;
     /// Make save string for crops; return None for tools
     fn to_save_string(&self) -> Option<String>;
+    /// Value gene of crops and seeds, which sets what they sell for;
+    /// None for everything else
+    fn value_gene(&self) -> Option<f32> {
+        None
+    }
+    /// Texture path of the map item this becomes when placed, for
+    /// sprinklers, flower beds and protections; None for everything else
+    fn placed_tex_path(&self) -> Option<&'static str> {
+        None
+    }
 }
 
 fn main() {
@@ -131,6 +160,16 @@ fn main() {
         .load_texture("src/images/Background_Tileset.png")
         .unwrap();
 
+    let hud_font = texture_creator.load_texture(hud::FONT_TEX_PATH).unwrap();
+
+    // Pest colonies and disease symptoms drawn over the crops
+    let colony_texture = texture_creator
+        .load_texture("src/images/pest_colony.png")
+        .unwrap();
+    let symptoms_texture = texture_creator
+        .load_texture(disease::SYMPTOMS_TEX_PATH)
+        .unwrap();
+
     // Roll group credits
     // let _ = roll_credits(&mut wincan, &texture_creator, r);
     // roll_credits(&mut wincan, &texture_creator, r).unwrap();
@@ -141,23 +180,9 @@ fn main() {
 
     let _menu_location = 0;
 
-    let mut p = player::Player::new(
-        Rect::new(
-            (BG_W / 2 - PLAYER_WIDTH / 2) as i32,
-            (BG_H / 2 - PLAYER_HEIGHT / 2) as i32,
-            PLAYER_WIDTH,
-            PLAYER_HEIGHT,
-        ),
-        texture_creator
-            .load_texture("src/images/farmer.png")
-            .unwrap(),
-        texture_creator
-            .load_texture("src/images/farmer_tools.png")
-            .unwrap(),
-        &texture_creator,
-    );
-
-    let mut pest_pop = save_load::load_pests();
+    let config = config::Config::load(config::CONFIG_FILE);
+    let mut pest_pops = save_load::load_pests(&config);
+    let mut predators = save_load::load_predators();
 
     let _crop_vec: Vec<crop::Crop> = Vec::new();
 
@@ -168,9 +193,10 @@ fn main() {
         &crop_texture,
         &rotten_texture,
         &tile_texture,
+        &pest_pops,
     );
     let mut pop = home_tup.0;
-    let item_vec = home_tup.1;
+    let mut item_vec = home_tup.1;
 
     // Load market
     let market_tup = save_load::load_market(
@@ -182,47 +208,57 @@ fn main() {
     let m_pop = market_tup.0;
     let m_item_vec = market_tup.1;
 
+    let mut p = player::Player::new(
+        Rect::new(
+            (pop.pixel_width() / 2 - PLAYER_WIDTH / 2) as i32,
+            (pop.pixel_height() / 2 - PLAYER_HEIGHT / 2) as i32,
+            PLAYER_WIDTH,
+            PLAYER_HEIGHT,
+        ),
+        texture_creator
+            .load_texture("src/images/farmer.png")
+            .unwrap(),
+        texture_creator
+            .load_texture("src/images/farmer_tools.png")
+            .unwrap(),
+        &texture_creator,
+    );
+
     // Load inventory
-    save_load::load_inventory(p.get_inventory(), &crop_texture, &rotten_texture);
+    p.set_money(save_load::load_money(&config));
+    save_load::load_inventory(
+        p.get_inventory(),
+        &texture_creator,
+        &crop_texture,
+        &rotten_texture,
+    );
 
-    // create a store with temp items
+    // create a store. Seed and crop prices come from the economy.
     let _seed_textures = texture_creator
         .load_texture("src/images/Crop_Tileset.png")
         .unwrap();
-    let store_item_0 = MarketItem::new(0, 10, 3, 4, Rect::new(0, 0, 80, 80), CropType::Carrot, 0);
-    let store_item_1 = MarketItem::new(7, 12, 2, 3, Rect::new(0, 80, 80, 80), CropType::Corn, 0);
-    let store_item_2 =
-        MarketItem::new(14, 11, 4, 5, Rect::new(0, 160, 80, 80), CropType::Potato, 0);
-    let store_item_3 = MarketItem::new(
-        21,
-        15,
-        6,
-        7,
-        Rect::new(0, 240, 80, 80),
-        CropType::Lettuce,
-        0,
-    );
-    let store_item_4 =
-        MarketItem::new(28, 10, 3, 4, Rect::new(240, 0, 80, 80), CropType::Carrot, 3);
-    let store_item_5 = MarketItem::new(35, 12, 2, 3, Rect::new(240, 80, 80, 80), CropType::Corn, 3);
-    let store_item_6 = MarketItem::new(
-        42,
-        11,
-        4,
-        5,
-        Rect::new(240, 160, 80, 80),
-        CropType::Potato,
-        3,
-    );
-    let store_item_7 = MarketItem::new(
-        49,
-        15,
-        6,
-        7,
-        Rect::new(240, 240, 80, 80),
-        CropType::Lettuce,
-        3,
-    );
+    let store_item_0 = MarketItem::new(0, 10, Rect::new(0, 0, 80, 80), CropType::Carrot, 0);
+    let store_item_1 = MarketItem::new(7, 12, Rect::new(0, 80, 80, 80), CropType::Corn, 0);
+    let store_item_2 = MarketItem::new(14, 11, Rect::new(0, 160, 80, 80), CropType::Potato, 0);
+    let store_item_3 = MarketItem::new(21, 15, Rect::new(0, 240, 80, 80), CropType::Lettuce, 0);
+    let store_item_4 = MarketItem::new(28, 10, Rect::new(240, 0, 80, 80), CropType::Carrot, 3);
+    let store_item_5 = MarketItem::new(35, 12, Rect::new(240, 80, 80, 80), CropType::Corn, 3);
+    let store_item_6 = MarketItem::new(42, 11, Rect::new(240, 160, 80, 80), CropType::Potato, 3);
+    let store_item_7 = MarketItem::new(49, 15, Rect::new(240, 240, 80, 80), CropType::Lettuce, 3);
+
+    let store_item_8 = MarketItem::new_sprinkler(56, 5, 20, 25, sprinkler::SprinklerTier::Basic);
+    let store_item_9 = MarketItem::new_sprinkler(63, 3, 45, 55, sprinkler::SprinklerTier::Quality);
+    let store_item_10 = MarketItem::new_sprinkler(70, 2, 90, 110, sprinkler::SprinklerTier::Deluxe);
+    let store_item_11 = MarketItem::new_land(77, 3, 300, 400);
+    let store_item_12 = MarketItem::new_pesticide(84, 20, 8, 12);
+    let store_item_13 = MarketItem::new_flower_bed(91, 10, 15, 20);
+    let store_item_14 =
+        MarketItem::new_protection(98, 3, 40, 50, protection::ProtectionKind::Scarecrow);
+    let store_item_15 =
+        MarketItem::new_protection(105, 10, 10, 15, protection::ProtectionKind::StickyTrap);
+    let store_item_16 =
+        MarketItem::new_protection(112, 5, 25, 30, protection::ProtectionKind::RowCover);
+    let store_item_17 = MarketItem::new_fungicide(119, 20, 10, 14);
 
     let mut market_items = vec![
         store_item_0,
@@ -233,9 +269,24 @@ fn main() {
         store_item_5,
         store_item_6,
         store_item_7,
+        store_item_8,
+        store_item_9,
+        store_item_10,
+        store_item_11,
+        store_item_12,
+        store_item_13,
+        store_item_14,
+        store_item_15,
+        store_item_16,
+        store_item_17,
     ];
 
-    let mut store = store::Store::new(8, &mut market_items);
+    // Prices in the market come from the valley's economy, and what's
+    // left on the shelves from the save
+    let mut economy = save_load::load_economy();
+    save_load::load_stock(&mut market_items);
+    let mut store = store::Store::new(18, &mut market_items);
+    store.update_prices(&economy);
 
     let mut in_area = Area::Home;
     // Things that might be used every frame but should only be loaded once:
@@ -245,6 +296,12 @@ fn main() {
 
     // enum used to pause the game while any menu is up.
     let mut in_menu: Option<Menu> = None;
+    let mut clock = clock::WorldClock::new(save_load::load_calendar());
+    let mut weather = save_load::load_weather(clock.get_calendar().get_season());
+    // When the player last tried to use a closed store
+    let mut store_closed_at: Option<Instant> = None;
+    let mut stats_view = stats_screen::StatsView::new();
+    let mut sell_screen = sell::SellScreen::new();
     'gameloop: loop {
         for event in event_pump.poll_iter() {
             match event {
@@ -253,9 +310,18 @@ fn main() {
                     keycode: Some(Keycode::Escape),
                     ..
                 } => {
-                    save_load::save_home(pop, item_vec);
+                    save_load::save_home(
+                        pop,
+                        item_vec,
+                        clock.get_calendar(),
+                        &weather,
+                        &pest_pops,
+                        p.get_money(),
+                        &economy,
+                        &store,
+                    );
                     save_load::save_inventory(p.get_inventory());
-                    save_load::save_pests(pest_pop);
+                    save_load::save_pests(&pest_pops, &predators);
                     break 'gameloop;
                 }
                 _ => {}
@@ -268,6 +334,28 @@ fn main() {
             .filter_map(Keycode::from_scancode)
             .collect();
 
+        clock.tick(in_menu.is_some());
+        if in_menu.is_none() && clock.passed_out() {
+            sleep_menu::pass_out(
+                &mut wincan,
+                &mut p,
+                &mut pop,
+                &item_vec,
+                r,
+                &mut pest_pops,
+                &mut predators,
+                &mut clock,
+                &mut weather,
+                &mut economy,
+                &mut store,
+            );
+            in_area = Area::Home;
+            p.set_position(
+                (pop.pixel_width() / 2 - PLAYER_WIDTH / 2) as i32,
+                (pop.pixel_height() / 2 - PLAYER_HEIGHT / 2) as i32,
+            );
+        }
+
         let mut x_deltav_f: f32 = 0.0;
         let mut y_deltav_f: f32 = 0.0;
 
@@ -287,7 +375,7 @@ fn main() {
                     x_deltav_f += player::ACCEL_RATE;
                 }
                 if event_pump.mouse_state().left() || keystate.contains(&Keycode::C) {
-                    let coordinates = p.get_facing();
+                    let coordinates = p.get_facing((pop.width(), pop.height()));
                     p.set_tooluse(true);
 
                     // Use inventory slot function
@@ -295,7 +383,12 @@ fn main() {
                     // This is done when a fully grown crop is used by the hand
                     match in_area {
                         Area::Home => {
+                            let before = tile_state(&pop, coordinates);
                             let result = p.use_inventory(coordinates, &mut pop);
+                            // Tilling, watering, planting and harvesting take time
+                            if result.is_some() || tile_state(&pop, coordinates) != before {
+                                clock.advance(clock::ACTION_MINUTES);
+                            }
                             match result {
                                 Some((Some(t), Some(g), child)) => {
                                     //Return multiple seeds from harvesting a plant
@@ -343,6 +436,17 @@ fn main() {
                                 None => (),
                                 _ => (),
                             };
+
+                            // Place a sprinkler, flower bed or protection if one is selected
+                            if sprinkler::can_place(coordinates, &pop, &item_vec) {
+                                if let Some(tex_path) = p.get_inventory().take_placeable() {
+                                    item_vec.push(item::Item::placed(
+                                        tex_path,
+                                        coordinates,
+                                        &texture_creator,
+                                    ));
+                                }
+                            }
                         }
                         Area::Market => (),
                     }
@@ -380,6 +484,30 @@ fn main() {
                 if keystate.contains(&Keycode::Minus) {
                     p.set_selected(10);
                 }
+                if keystate.contains(&Keycode::Equals) {
+                    p.set_selected(inventory::SPRINKLER_SLOT);
+                }
+                if keystate.contains(&Keycode::Backspace) {
+                    p.set_selected(inventory::PESTICIDE_SLOT);
+                }
+                if keystate.contains(&Keycode::Backslash) {
+                    p.set_selected(inventory::FLOWER_SLOT);
+                }
+                if keystate.contains(&Keycode::LeftBracket) {
+                    p.set_selected(inventory::SCARECROW_SLOT);
+                }
+                if keystate.contains(&Keycode::RightBracket) {
+                    p.set_selected(inventory::TRAP_SLOT);
+                }
+                if keystate.contains(&Keycode::Semicolon) {
+                    p.set_selected(inventory::COVER_SLOT);
+                }
+                if keystate.contains(&Keycode::Quote) {
+                    p.set_selected(inventory::FUNGICIDE_SLOT);
+                }
+                if keystate.contains(&Keycode::G) {
+                    in_menu = Some(Menu::PestStats);
+                }
             }
             //I know having 3 seperate methods isn't really 'modular' but the code has already been written for each and they all require different things so... this is it
             Some(Menu::Sleep) => {
@@ -389,8 +517,14 @@ fn main() {
                     keystate,
                     &mut p,
                     &mut pop,
+                    &item_vec,
                     r,
-                    &mut pest_pop,
+                    &mut pest_pops,
+                    &mut predators,
+                    &mut clock,
+                    &mut weather,
+                    &mut economy,
+                    &mut store,
                 );
             }
             Some(Menu::ToMarket) => {
@@ -402,6 +536,9 @@ fn main() {
                     Some(in_area),
                 );
                 in_menu = menu_and_area_tup.0;
+                if menu_and_area_tup.1 != in_area {
+                    clock.advance(clock::TRAVEL_MINUTES);
+                }
                 in_area = menu_and_area_tup.1;
             }
             Some(Menu::ToHome) => {
@@ -413,12 +550,68 @@ fn main() {
                     Some(in_area),
                 );
                 in_menu = menu_and_area_tup.0;
+                if menu_and_area_tup.1 != in_area {
+                    clock.advance(clock::TRAVEL_MINUTES);
+                }
                 in_area = menu_and_area_tup.1;
             }
+            Some(Menu::PestStats) => {
+                if keystate.contains(&Keycode::Q) {
+                    in_menu = None;
+                }
+                if keystate.contains(&Keycode::Left) {
+                    stats_view.cycle_species(-1, pest_pops.len());
+                    thread::sleep(Duration::from_millis(160));
+                }
+                if keystate.contains(&Keycode::Right) {
+                    stats_view.cycle_species(1, pest_pops.len());
+                    thread::sleep(Duration::from_millis(160));
+                }
+                if keystate.contains(&Keycode::Up) {
+                    stats_view.cycle_gene(-1);
+                    thread::sleep(Duration::from_millis(160));
+                }
+                if keystate.contains(&Keycode::Down) {
+                    stats_view.cycle_gene(1);
+                    thread::sleep(Duration::from_millis(160));
+                }
+            }
+            Some(Menu::Sell) => {
+                if !clock.store_open() {
+                    in_menu = None;
+                    store_closed_at = Some(Instant::now());
+                }
+                if keystate.contains(&Keycode::Q) {
+                    in_menu = Some(Menu::Shop);
+                    thread::sleep(Duration::from_millis(160));
+                }
+                let num_stacks = p.get_inventory().crop_stacks().len();
+                if keystate.contains(&Keycode::Up) {
+                    sell_screen.navigate(-1, num_stacks);
+                    thread::sleep(Duration::from_millis(160));
+                }
+                if keystate.contains(&Keycode::Down) {
+                    sell_screen.navigate(1, num_stacks);
+                    thread::sleep(Duration::from_millis(160));
+                }
+                if keystate.contains(&Keycode::P) {
+                    sell_screen.sell(&mut p, &mut economy);
+                    thread::sleep(Duration::from_millis(160));
+                }
+            }
             Some(Menu::Shop) => {
+                if !clock.store_open() {
+                    in_menu = None;
+                    store_closed_at = Some(Instant::now());
+                }
                 if keystate.contains(&Keycode::Q) {
                     in_menu = None;
                 }
+                // The stall buys as well as sells. Tab, as S walks down
+                if keystate.contains(&Keycode::Tab) {
+                    in_menu = Some(Menu::Sell);
+                    thread::sleep(Duration::from_millis(160));
+                }
                 if keystate.contains(&Keycode::Up) {
                     store.navigate(-1);
                     thread::sleep(Duration::from_millis(160));
@@ -439,20 +632,75 @@ fn main() {
                     let _new_crop_texture = texture_creator
                         .load_texture("src/images/Crop_Tileset.png")
                         .unwrap();
-                    let _p = store.confirm_purchase();
-                    if let Some((a, t, g)) = _p {
-                        for _ in 0..a {
-                            let _c = crop::Crop::new(
-                                Rect::new(0, 0, 0, 0),
-                                g as u8,
-                                &crop_texture,
-                                &rotten_texture,
-                                false,
-                                t,
-                                Some(genes::Genes::new()),
-                            );
-                            p.add_item(_c);
+                    let _p = store.confirm_purchase(p.get_money(), pop.plots_left());
+                    if let Some((_, cost)) = _p {
+                        p.charge(cost);
+                        clock.advance(clock::SHOPPING_MINUTES);
+                    }
+                    match _p.map(|(purchase, _)| purchase) {
+                        Some(store::Purchase::Crops(a, t, g)) => {
+                            for _ in 0..a {
+                                let mut _c = crop::Crop::new(
+                                    Rect::new(0, 0, 0, 0),
+                                    g as u8,
+                                    &crop_texture,
+                                    &rotten_texture,
+                                    false,
+                                    t,
+                                    Some(genes::Genes::new()),
+                                );
+                                // Seeds from outside the farm can carry pest eggs in
+                                _c.set_eggs(immigration::seed_eggs(&pest_pops));
+                                p.add_item(_c);
+                            }
+                        }
+                        Some(store::Purchase::Land(a)) => {
+                            let tile_map = terrain::TileMap::load(save_load::HOME_MAP);
+                            // The store only sells plots the farm has room for
+                            for _ in 0..a {
+                                pop.add_plot(
+                                    &tile_map,
+                                    &tile_texture,
+                                    &crop_texture,
+                                    &rotten_texture,
+                                );
+                            }
                         }
+                        Some(store::Purchase::Pesticide(a)) => {
+                            for _ in 0..a {
+                                p.get_inventory()
+                                    .add_pesticide(pesticide::Pesticide::new(&texture_creator));
+                            }
+                        }
+                        Some(store::Purchase::FlowerBeds(a)) => {
+                            for _ in 0..a {
+                                p.get_inventory()
+                                    .add_flower_bed(flower_bed::FlowerBed::new(&texture_creator));
+                            }
+                        }
+                        Some(store::Purchase::Protections(a, kind)) => {
+                            for _ in 0..a {
+                                p.get_inventory().add_protection(
+                                    kind,
+                                    protection::Protection::new(kind, &texture_creator),
+                                );
+                            }
+                        }
+                        Some(store::Purchase::Fungicide(a)) => {
+                            for _ in 0..a {
+                                p.get_inventory()
+                                    .add_fungicide(disease::Fungicide::new(&texture_creator));
+                            }
+                        }
+                        Some(store::Purchase::Sprinklers(a, tier)) => {
+                            for _ in 0..a {
+                                p.get_inventory().add_sprinkler(sprinkler::Sprinkler::new(
+                                    tier,
+                                    &texture_creator,
+                                ));
+                            }
+                        }
+                        None => {}
                     }
                     in_menu = None;
                     thread::sleep(Duration::from_millis(160));
@@ -467,10 +715,15 @@ fn main() {
         match in_area {
             Area::Home => {
                 // X
-                p.update_pos_x(player_vel, (0, (BG_W - TILE_SIZE) as i32));
+                let x_bounds = (0, (pop.pixel_width() - TILE_SIZE) as i32);
+                let y_bounds = (0, (pop.pixel_height() - TILE_SIZE) as i32);
+                p.update_pos_x(player_vel, x_bounds);
+                if pop.collides(p.get_pos()) {
+                    p.stay_still_x(player_vel, x_bounds);
+                }
                 for item in &item_vec {
                     if p.check_collision(&item.pos()) {
-                        p.stay_still_x(player_vel, (0, (BG_W - TILE_SIZE) as i32));
+                        p.stay_still_x(player_vel, x_bounds);
                         if item.tex_path() == "src/images/house.png" {
                             in_menu = Some(Menu::Sleep);
                         } else if item.tex_path() == "src/images/go_market.png" {
@@ -482,10 +735,13 @@ fn main() {
                 }
 
                 //Y
-                p.update_pos_y(player_vel, (0, (BG_W - TILE_SIZE) as i32));
+                p.update_pos_y(player_vel, y_bounds);
+                if pop.collides(p.get_pos()) {
+                    p.stay_still_y(player_vel, y_bounds);
+                }
                 for item in &item_vec {
                     if p.check_collision(&item.pos()) {
-                        p.stay_still_y(player_vel, (0, (BG_W - TILE_SIZE) as i32));
+                        p.stay_still_y(player_vel, y_bounds);
                         if item.tex_path() == "src/images/house.png" {
                             in_menu = Some(Menu::Sleep);
                         } else if item.tex_path() == "src/images/go_market.png" {
@@ -497,21 +753,14 @@ fn main() {
                 }
             }
             Area::Market => {
-                market::update_market_pos(&mut p, &m_item_vec, player_vel, &mut in_menu)
+                market::update_market_pos(&mut p, &m_pop, &m_item_vec, player_vel, &mut in_menu)
             }
         }
 
         // Determine part of background to draw
         let cur_bg = match in_area {
-            Area::Home => Rect::new(
-                ((p.x() + ((p.width() / 2) as i32)) - ((CAM_W / 2) as i32))
-                    .clamp(0, (BG_W - CAM_W) as i32),
-                ((p.y() + ((p.height() / 2) as i32)) - ((CAM_H / 2) as i32))
-                    .clamp(0, (BG_H - CAM_H) as i32),
-                CAM_W,
-                CAM_H,
-            ),
-            Area::Market => market::background_to_draw(&p),
+            Area::Home => market::background_to_draw(&p, &pop),
+            Area::Market => market::background_to_draw(&p, &m_pop),
         };
 
         // Convert player map position to be camera-relative
@@ -528,7 +777,7 @@ fn main() {
         // Draw tiles
         match in_area {
             Area::Home => {
-                let coordinates = p.get_facing();
+                let coordinates = p.get_facing((pop.width(), pop.height()));
                 for crop_tile in pop.get_vec().iter().flatten() {
                     let x_pos = crop_tile.tile.x() - cur_bg.x();
                     let y_pos = crop_tile.tile.y() - cur_bg.y();
@@ -547,6 +796,11 @@ fn main() {
                         wincan
                             .copy(crop_tile.tile.texture(), crop_tile.tile.src(), cur_tile)
                             .unwrap();
+                        // Tint sprayed tiles until the pesticide wears off
+                        if crop_tile.pesticide > 0 {
+                            wincan.set_draw_color(Color::RGBA(120, 200, 80, 60));
+                            wincan.fill_rect(cur_tile).unwrap();
+                        }
                         if (
                             crop_tile.tile.x() / TILE_SIZE as i32,
                             crop_tile.tile.y() / TILE_SIZE as i32,
@@ -566,15 +820,45 @@ fn main() {
                 }
 
                 // Draw crops
-                for _x in 0..((BG_W / TILE_SIZE) as i32 + 1) {
-                    for _y in 0..((BG_H / TILE_SIZE) as i32 + 1) {
-                        let _c = pop.get_crop_with_index(_x as u32, _y as u32);
+                for _x in 0..pop.width() {
+                    for _y in 0..pop.height() {
+                        let _c = pop.get_crop_with_index(_x, _y);
                         match _c.get_crop_type() {
                             "None" => {}
                             _ => {
                                 wincan = _c.print_crop(cur_bg.x(), cur_bg.y(), wincan);
                             }
                         }
+                        // Draw the symptoms of any disease over the crop
+                        if let Some(d) = pop.get_disease(_x, _y) {
+                            wincan
+                                .copy(
+                                    &symptoms_texture,
+                                    d.src(),
+                                    Rect::new(
+                                        _c.get_x() - cur_bg.x(),
+                                        _c.get_y() - cur_bg.y(),
+                                        TILE_SIZE,
+                                        TILE_SIZE,
+                                    ),
+                                )
+                                .unwrap();
+                        }
+                        // Draw pests on top of the crop they're eating
+                        if let Some(colony) = pop.get_colony(_x, _y) {
+                            wincan
+                                .copy(
+                                    &colony_texture,
+                                    colony.src(),
+                                    Rect::new(
+                                        _c.get_x() - cur_bg.x(),
+                                        _c.get_y() - cur_bg.y(),
+                                        TILE_SIZE,
+                                        TILE_SIZE,
+                                    ),
+                                )
+                                .unwrap();
+                        }
                     }
                 }
             }
@@ -586,6 +870,37 @@ fn main() {
         p.draw(&mut wincan, player_cam_pos);
         p.set_tooluse(false);
 
+        clock.draw_overlay(&mut wincan, r);
+        clock.draw(&mut wincan, &hud_font);
+        hud::draw_panel(
+            &mut wincan,
+            &hud_font,
+            &[format!("${}", p.get_money())],
+            30,
+            10,
+            4,
+        );
+        if let Some(t) = store_closed_at {
+            if t.elapsed() < NOTICE_TIME {
+                let text = format!(
+                    "Store closed. Open {}-{}",
+                    clock::hour_string(clock::STORE_OPEN),
+                    clock::hour_string(clock::STORE_CLOSE)
+                );
+                let scale = 5;
+                hud::draw_panel(
+                    &mut wincan,
+                    &hud_font,
+                    &[text.clone()],
+                    (CAM_W - hud::text_width(&text, scale)) as i32 / 2,
+                    (CAM_H / 2) as i32,
+                    scale,
+                );
+            } else {
+                store_closed_at = None;
+            }
+        }
+
         //ui.draw(&mut wincan);
 
         match in_menu {
@@ -597,6 +912,17 @@ fn main() {
                 wincan
                     .copy(&sleep_box, None, Rect::new(400, 400, 600, 180))
                     .unwrap();
+                hud::draw_panel(
+                    &mut wincan,
+                    &hud_font,
+                    &[
+                        format!("Today: {}", weather.get_today()),
+                        format!("Tomorrow: {}", weather.get_tomorrow()),
+                    ],
+                    400,
+                    590,
+                    4,
+                );
             }
             Some(Menu::ToMarket) => {
                 let go_box = texture_creator
@@ -617,8 +943,28 @@ fn main() {
             Some(Menu::Shop) => {
                 store.draw(&mut wincan);
             }
+            Some(Menu::Sell) => {
+                let money = p.get_money();
+                sell_screen.draw(&mut wincan, &hud_font, p.get_inventory(), money, &economy);
+            }
+            Some(Menu::PestStats) => {
+                stats_view.draw(&mut wincan, &hud_font, &pest_pops);
+            }
         }
 
         wincan.present();
     } // end gameloop
 }
+
+/// Snapshot of a home tile, used to tell whether using the inventory
+/// actually did something
+fn tile_state(pop: &population::Population, (x, y): (i32, i32)) -> (bool, bool, CropType, u8) {
+    let t = pop.get_tile_with_index(x as u32, y as u32);
+    let c = pop.get_crop_with_index(x as u32, y as u32);
+    (
+        t.tilled(),
+        c.get_watered(),
+        c.get_crop_type_enum(),
+        c.get_stage(),
+    )
+}
@@ -1,7 +1,5 @@
 use crate::Area;
 use crate::Menu;
-use crate::BG_H;
-use crate::BG_W;
 use crate::TILE_SIZE;
 use crate::{item, population, Animation, CAM_H, CAM_W};
 
@@ -92,14 +90,18 @@ pub fn start_market_transition_menu<'a>(
 
 pub fn update_market_pos(
     p: &mut Player,
+    m_pop: &population::Population,
     m_item_vec: &Vec<item::Item>,
     player_vel: (i32, i32),
     in_menu: &mut Option<Menu>,
 ) {
-    p.update_pos_x(player_vel, (0, (BG_W - TILE_SIZE) as i32));
+    p.update_pos_x(player_vel, (0, (m_pop.pixel_width() - TILE_SIZE) as i32));
+    if m_pop.collides(p.get_pos()) {
+        p.stay_still_x(player_vel, (0, (m_pop.pixel_width() - TILE_SIZE) as i32));
+    }
     for item in m_item_vec {
         if p.check_collision(&item.pos()) {
-            p.stay_still_x(player_vel, (0, (BG_W - TILE_SIZE) as i32));
+            p.stay_still_x(player_vel, (0, (m_pop.pixel_width() - TILE_SIZE) as i32));
             if item.tex_path() == "src/images/marketstall.png" {
                 *in_menu = Some(Menu::Shop);
             } else if item.tex_path() == "src/images/go_home.png" {
@@ -109,10 +111,13 @@ pub fn update_market_pos(
     }
 
     //Y
-    p.update_pos_y(player_vel, (0, (BG_W - TILE_SIZE) as i32));
+    p.update_pos_y(player_vel, (0, (m_pop.pixel_height() - TILE_SIZE) as i32));
+    if m_pop.collides(p.get_pos()) {
+        p.stay_still_y(player_vel, (0, (m_pop.pixel_height() - TILE_SIZE) as i32));
+    }
     for item in m_item_vec {
         if p.check_collision(&item.pos()) {
-            p.stay_still_y(player_vel, (0, (BG_W - TILE_SIZE) as i32));
+            p.stay_still_y(player_vel, (0, (m_pop.pixel_height() - TILE_SIZE) as i32));
             if item.tex_path() == "src/images/marketstall.png" {
                 *in_menu = Some(Menu::Shop);
             } else if item.tex_path() == "src/images/go_home.png" {
@@ -122,11 +127,14 @@ pub fn update_market_pos(
     }
 }
 
-pub fn background_to_draw(p: &Player) -> Rect {
+/// Part of an area's background the camera should show, centered on
+/// the player and kept inside the area's bounds
+pub fn background_to_draw(p: &Player, pop: &population::Population) -> Rect {
     Rect::new(
-        ((p.x() + ((p.width() / 2) as i32)) - ((CAM_W / 2) as i32)).clamp(0, (BG_W - CAM_W) as i32),
+        ((p.x() + ((p.width() / 2) as i32)) - ((CAM_W / 2) as i32))
+            .clamp(0, (pop.pixel_width() as i32 - CAM_W as i32).max(0)),
         ((p.y() + ((p.height() / 2) as i32)) - ((CAM_H / 2) as i32))
-            .clamp(0, (BG_H - CAM_H) as i32),
+            .clamp(0, (pop.pixel_height() as i32 - CAM_H as i32).max(0)),
         CAM_W,
         CAM_H,
     )
@@ -1,5 +1,5 @@
 use crate::agent::EconAgent;
-use crate::commodities::CommodityKind;
+use crate::commodities::{Commodities, CommodityKind};
 
 extern crate rand;
 
@@ -98,6 +98,30 @@ impl TradeTable {
     pub fn clear(&mut self, c: CommodityKind) {
         self.trades[c as usize].clear();
     }
+
+    /// Remove every trade for `c`
+    pub fn drain(&mut self, c: CommodityKind) -> Vec<Trade> {
+        std::mem::take(&mut self.trades[c as usize])
+    }
+
+    /// Total quantity of every trade for `c`
+    pub fn quantity(&self, c: CommodityKind) -> i32 {
+        self.trades[c as usize].iter().map(|t| t.quantity).sum()
+    }
+}
+
+/// How the orders for one commodity went in a round of trading
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct ClearingReport {
+    pub commodity: CommodityKind,
+    /// Units that changed hands
+    pub volume: i32,
+    /// Average price the units changed hands at, or 0 if none did
+    pub mean_price: f32,
+    /// Units bid for that no one sold
+    pub unfilled_bids: i32,
+    /// Units asked for that no one bought
+    pub unfilled_asks: i32,
 }
 
 pub struct MarketHouse {
@@ -113,7 +137,25 @@ impl MarketHouse {
         }
     }
 
-    fn resolve(&mut self, c: CommodityKind, agents: &mut Vec<EconAgent>) {
+    pub fn submit_asks(&mut self, asks: TradeSubmission) {
+        self.ask_table.add(asks);
+    }
+
+    pub fn submit_bids(&mut self, bids: TradeSubmission) {
+        self.bid_table.add(bids);
+    }
+
+    /// Quantity asked and bid for `c` this round, before resolving
+    pub fn offered(&self, c: CommodityKind) -> (i32, i32) {
+        (self.ask_table.quantity(c), self.bid_table.quantity(c))
+    }
+
+    fn resolve(
+        &mut self,
+        c: CommodityKind,
+        agents: &mut Vec<EconAgent>,
+        com: &mut Commodities,
+    ) -> ClearingReport {
         // Explicitly shuffle so that agents don't have an unfair advantage for
         // being born earlier.
         self.bid_table.shuffle(c);
@@ -123,6 +165,9 @@ impl MarketHouse {
         // that removing isn't quadratic time.
         self.bid_table.sort_asc(c);
         self.ask_table.sort_desc(c);
+        let (asked, bid) = self.offered(c);
+        let mut money_traded = 0.0;
+        let mut units_traded = 0;
         while !self.bid_table.is_empty(c) && !self.ask_table.is_empty(c) {
             let mut buy = self.bid_table.pop(c);
             let mut sell = self.ask_table.pop(c);
@@ -131,42 +176,99 @@ impl MarketHouse {
             if q > 0 {
                 buy.reduce(q);
                 let buyer = &mut agents[buy.agent_idx];
-                buyer.buy("this is not correct".into(), q as f32, p);
-                // TODO: buy doesn't update price beliefs
+                buyer.buy(c, q as f32, p);
 
                 sell.reduce(q);
                 let seller = &mut agents[sell.agent_idx];
-                seller.sell("this is not correct".into(), q as f32, p);
-                // TODO: sell doesn't update price beliefs
+                seller.sell(c, q as f32, p);
+
+                money_traded += p * q as f32;
+                units_traded += q;
             }
             // The paper only removes trades when they're done. We remove
             // trades every time and push them back on if they aren't clear.
             // No difference, just maybe convenience wrt borrow checker.
             if buy.quantity > 0 {
-                self.ask_table.push(c, buy);
+                self.bid_table.push(c, buy);
             }
             if sell.quantity > 0 {
-                self.bid_table.push(c, sell);
+                self.ask_table.push(c, sell);
             }
         }
-        // TODO: remaining offers are rejected
-        self.bid_table.clear(c);
-        self.ask_table.clear(c);
-    }
-
-    pub fn resolve_all(&mut self, agents: &mut Vec<EconAgent>) {
-        let cs = [
-            CommodityKind::CarrotSeed,
-            CommodityKind::CarrotCrop,
-            CommodityKind::CornSeed,
-            CommodityKind::CornCrop,
-            CommodityKind::PotatoSeed,
-            CommodityKind::PotatoCrop,
-            CommodityKind::LettuceSeed,
-            CommodityKind::LettuceCrop,
-        ];
-        for c in cs {
-            self.resolve(c, agents);
+        let mut report = ClearingReport {
+            commodity: c,
+            volume: units_traded,
+            mean_price: 0.0,
+            unfilled_bids: 0,
+            unfilled_asks: 0,
+        };
+        for t in self.bid_table.drain(c) {
+            report.unfilled_bids += t.quantity;
+            agents[t.agent_idx].reject_bid(c, t.price);
+        }
+        for t in self.ask_table.drain(c) {
+            report.unfilled_asks += t.quantity;
+            agents[t.agent_idx].reject_ask(c, t.price);
         }
+
+        if let Some(commodity) = com.get_mut(c) {
+            if units_traded > 0 {
+                report.mean_price = money_traded / units_traded as f32;
+                commodity.update(report.mean_price, bid as f32);
+            }
+            commodity.add_bid(bid as f32);
+            commodity.add_ask(asked as f32);
+        }
+        report
+    }
+
+    /// Match up every commodity's bids and asks, returning a report for
+    /// each commodity in the order of `CommodityKind::ALL`
+    pub fn resolve_all(
+        &mut self,
+        agents: &mut Vec<EconAgent>,
+        com: &mut Commodities,
+    ) -> Vec<ClearingReport> {
+        CommodityKind::ALL
+            .iter()
+            .map(|c| self.resolve(*c, agents, com))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn trades_reach_the_right_stock_and_are_reported() {
+        let mut com = Commodities::new();
+        com.init();
+        // Seed growers start with crops, farmers with seeds
+        let mut seller = EconAgent::new();
+        seller.init_agent(100.0, &vec![CommodityKind::CarrotSeed], 5.0, 20.0, &mut com);
+        let mut buyer = EconAgent::new();
+        buyer.init_agent(100.0, &vec![CommodityKind::CarrotCrop], 5.0, 20.0, &mut com);
+        let mut agents = vec![seller, buyer];
+
+        let c = CommodityKind::CarrotCrop;
+        let mut market = MarketHouse::new();
+        let mut asks = TradeSubmission::new();
+        asks.add(c, Trade::new(c, 5.0, 3, 0));
+        market.submit_asks(asks);
+        let mut bids = TradeSubmission::new();
+        bids.add(c, Trade::new(c, 7.0, 5, 1));
+        market.submit_bids(bids);
+
+        let reports = market.resolve_all(&mut agents, &mut com);
+        let report = reports.iter().find(|r| r.commodity == c).unwrap();
+        assert_eq!(report.volume, 3);
+        assert_eq!(report.mean_price, 6.0);
+        assert_eq!(report.unfilled_bids, 2);
+        assert_eq!(report.unfilled_asks, 0);
+        assert_eq!(agents[0].get_quantity(c), 2.0);
+        assert_eq!(agents[1].get_quantity(c), 3.0);
+        assert_eq!(agents[0].get_cash(), 118.0);
+        assert_eq!(agents[1].get_cash(), 82.0);
     }
 }
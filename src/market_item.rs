@@ -1,4 +1,8 @@
+use crate::commodities::CommodityKind;
 use crate::crop::CropType;
+use crate::economy::Economy;
+use crate::protection::ProtectionKind;
+use crate::sprinkler::SprinklerTier;
 
 use sdl2::rect::Rect;
 
@@ -7,6 +11,24 @@ use sdl2::rect::Rect;
 // SEED 2 -
 // SEED 3 -
 // SEED 4 -
+// SPRK 1 - 56
+// SPRK 2 - 63
+// SPRK 3 - 70
+// LAND - 77
+// PEST - 84
+// FLWR - 91
+// SCRW - 98
+// TRAP - 105
+// COVR - 112
+// FUNG - 119
+
+/// Most the price of an item goes up by when it's sold out, as a share
+/// of its usual price. It rises steadily as the stock runs down.
+const SOLD_OUT_MARKUP: f32 = 0.5;
+/// Highest price of a crop or seed compared to its market price
+const MAX_PRICE_SHARE: f32 = 1.25;
+/// Items put back on the shelf each night on top of the valley's surplus
+const BASE_RESTOCK: i32 = 1;
 
 pub struct MarketItem {
     pub item_label_offset: i32,
@@ -16,27 +38,214 @@ pub struct MarketItem {
     pub pos: Rect,
     pub crop: CropType,
     pub growth: i32,
+    pub good: Good,
+    /// Commodity of the valley's economy the item is, if any
+    commodity: Option<CommodityKind>,
+    /// Most of the item the stall keeps in stock
+    max_amount: i32,
+    /// Items put back on the shelf each night
+    restock_rate: i32,
+    /// Price range when the item is fully stocked
+    base_min: f32,
+    base_max: f32,
+}
+
+/// Kind of thing a market item sells
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum Good {
+    /// Seeds or crops; see `crop` and `growth`
+    Crop,
+    Sprinkler(SprinklerTier),
+    /// A plot of land next to the farm
+    Land,
+    Pesticide,
+    /// Flower beds that draw ladybugs to the farm
+    FlowerBed,
+    /// Scarecrows, sticky traps or row covers
+    Protection(ProtectionKind),
+    Fungicide,
 }
 
 impl MarketItem {
+    /// Creates a market item selling seeds, or fully grown crops if
+    /// `growth` is 3. Its prices come from the economy; see `update`.
     pub fn new(
         item_label_offset: i32,
         amount: i32,
-        min: i32,
-        max: i32,
         pos: Rect,
         crop: CropType,
         growth: i32,
     ) -> MarketItem {
+        let mut item = MarketItem::stocking(item_label_offset, amount, 0, 0, Good::Crop);
+        item.pos = pos;
+        item.crop = crop;
+        item.growth = growth;
+        item.commodity = CommodityKind::of_crop(crop, growth != 3);
+        item
+    }
+
+    /// A market item of any good with `amount` in stock, selling between
+    /// `min` and `max` when fully stocked
+    fn stocking(item_label_offset: i32, amount: i32, min: i32, max: i32, good: Good) -> MarketItem {
         MarketItem {
             item_label_offset,
             amount,
             min,
             max,
-            pos,
-            crop,
-            growth,
-            //texture,
+            pos: Rect::new(0, 0, 80, 80),
+            crop: CropType::None,
+            growth: 0,
+            good,
+            commodity: None,
+            max_amount: amount,
+            restock_rate: BASE_RESTOCK,
+            base_min: min as f32,
+            base_max: max as f32,
         }
     }
+
+    /// Creates a market item selling sprinklers of the given tier
+    pub fn new_sprinkler(
+        item_label_offset: i32,
+        amount: i32,
+        min: i32,
+        max: i32,
+        tier: SprinklerTier,
+    ) -> MarketItem {
+        MarketItem::stocking(item_label_offset, amount, min, max, Good::Sprinkler(tier))
+    }
+
+    /// Creates a market item selling plots of land for the farm
+    pub fn new_land(item_label_offset: i32, amount: i32, min: i32, max: i32) -> MarketItem {
+        MarketItem::stocking(item_label_offset, amount, min, max, Good::Land)
+    }
+
+    /// Creates a market item selling cans of pesticide
+    pub fn new_pesticide(item_label_offset: i32, amount: i32, min: i32, max: i32) -> MarketItem {
+        MarketItem::stocking(item_label_offset, amount, min, max, Good::Pesticide)
+    }
+
+    /// Creates a market item selling flower beds
+    pub fn new_flower_bed(item_label_offset: i32, amount: i32, min: i32, max: i32) -> MarketItem {
+        MarketItem::stocking(item_label_offset, amount, min, max, Good::FlowerBed)
+    }
+
+    /// Creates a market item selling protections of the given kind
+    pub fn new_protection(
+        item_label_offset: i32,
+        amount: i32,
+        min: i32,
+        max: i32,
+        kind: ProtectionKind,
+    ) -> MarketItem {
+        MarketItem::stocking(item_label_offset, amount, min, max, Good::Protection(kind))
+    }
+
+    /// Creates a market item selling bottles of fungicide
+    pub fn new_fungicide(item_label_offset: i32, amount: i32, min: i32, max: i32) -> MarketItem {
+        MarketItem::stocking(item_label_offset, amount, min, max, Good::Fungicide)
+    }
+
+    /// Take the going price and restock rate of the item's commodity
+    /// from the economy. Items the economy doesn't trade keep theirs.
+    pub fn update(&mut self, economy: &Economy) {
+        if let Some(c) = self.commodity {
+            let price = economy.get_price(c);
+            self.base_min = price;
+            self.base_max = price * MAX_PRICE_SHARE;
+            // What the valley's producers couldn't sell ends up at the stall
+            self.restock_rate = BASE_RESTOCK + economy.get_report(c).map_or(0, |r| r.unfilled_asks);
+        }
+        self.reprice();
+    }
+
+    /// Put tonight's stock back on the shelf
+    pub fn restock(&mut self) {
+        self.amount = (self.amount + self.restock_rate).min(self.max_amount);
+        self.reprice();
+    }
+
+    /// Set how many are on the shelf, e.g. from a save, up to what the
+    /// stall keeps in stock
+    pub fn set_amount(&mut self, amount: i32) {
+        self.amount = amount.clamp(0, self.max_amount);
+        self.reprice();
+    }
+
+    /// Take `amount` off the shelf after the player buys it
+    pub fn take(&mut self, amount: i32) {
+        self.amount = (self.amount - amount).max(0);
+        self.reprice();
+    }
+
+    /// Set the prices from the base prices, marked up the more of the
+    /// stock has been bought
+    fn reprice(&mut self) {
+        let sold = 1.0 - self.amount as f32 / self.max_amount.max(1) as f32;
+        let markup = 1.0 + SOLD_OUT_MARKUP * sold;
+        self.min = ((self.base_min * markup).ceil() as i32).max(1);
+        self.max = ((self.base_max * markup).ceil() as i32).max(self.min);
+    }
+
+    /// Path of the texture shown in the store's image box
+    pub fn tex_path(&self) -> &'static str {
+        match self.good {
+            Good::Crop => "src/images/Crop_Tileset.png",
+            Good::Sprinkler(tier) => tier.tex_path(),
+            Good::Land => "src/images/land_plot.png",
+            Good::Pesticide => crate::pesticide::TEX_PATH,
+            Good::FlowerBed => crate::flower_bed::TEX_PATH,
+            Good::Protection(kind) => kind.tex_path(),
+            Good::Fungicide => crate::disease::FUNGICIDE_TEX_PATH,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn buying_out_marks_up_price() {
+        let mut item = MarketItem::new_pesticide(84, 20, 8, 12);
+        item.take(10);
+        assert_eq!((item.min, item.max), (10, 15));
+        item.take(15);
+        assert_eq!(item.amount, 0);
+        assert_eq!((item.min, item.max), (12, 18));
+    }
+
+    #[test]
+    fn restock_stops_at_max_amount() {
+        let mut item = MarketItem::new_pesticide(84, 20, 8, 12);
+        item.take(20);
+        item.restock();
+        assert_eq!(item.amount, BASE_RESTOCK);
+        item.restock_rate = 50;
+        item.restock();
+        assert_eq!(item.amount, 20);
+        assert_eq!((item.min, item.max), (8, 12));
+    }
+
+    #[test]
+    fn economy_sets_price_and_restock_rate() {
+        let economy = Economy::new();
+        let c = CommodityKind::CarrotSeed;
+        let mut item = MarketItem::new(0, 10, Rect::new(0, 0, 80, 80), CropType::Carrot, 0);
+        item.update(&economy);
+        assert_eq!(item.base_min, economy.get_price(c));
+        assert_eq!(
+            item.restock_rate,
+            BASE_RESTOCK + economy.get_report(c).unwrap().unfilled_asks
+        );
+    }
+
+    #[test]
+    fn saved_amount_keeps_markup() {
+        let mut item = MarketItem::new_pesticide(84, 20, 8, 12);
+        item.set_amount(0);
+        assert_eq!((item.min, item.max), (12, 18));
+        item.set_amount(100);
+        assert_eq!(item.amount, 20);
+    }
 }
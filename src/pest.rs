@@ -1,12 +1,54 @@
-use crate::crop::{Crop, CropType};
-use crate::genes::GeneType::PestResistance;
-use rand;
+use crate::crop::CropType;
+use crate::rng;
 use rand::Rng;
 use rand_distr::{Distribution, Normal};
+use std::str::FromStr;
 
 const MEAN: f32 = 0.5;
 const STD_DEV: f32 = 0.1;
 const MUTATE_RATE: f32 = 0.02;
+/// Average pesticide resistance of a brand new pest
+const RESISTANCE_MEAN: f32 = 0.1;
+/// Number of genes a pest has
+pub const NUM_GENES: usize = 7;
+/// Average of each gene in a brand new pest, in the order `make_pest` takes them
+pub const BASE_MEANS: [f32; NUM_GENES] = [0.2, MEAN, MEAN, MEAN, MEAN, MEAN, RESISTANCE_MEAN];
+/// Nights an egg takes to hatch
+const EGG_NIGHTS: u32 = 2;
+/// Nights a larva takes to grow into an adult
+const LARVA_NIGHTS: u32 = 3;
+
+/// Stages of a pest's life
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum Stage {
+    /// Laid in the soil of a crop tile; can't attack or be sprayed
+    Egg,
+    Larva,
+    Adult,
+}
+
+impl FromStr for Stage {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "Egg" => Ok(Stage::Egg),
+            "Larva" => Ok(Stage::Larva),
+            "Adult" => Ok(Stage::Adult),
+            _ => Err(format!("invalid Stage: {}", s)),
+        }
+    }
+}
+
+impl std::fmt::Display for Stage {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Stage::Egg => write!(f, "Egg"),
+            Stage::Larva => write!(f, "Larva"),
+            Stage::Adult => write!(f, "Adult"),
+        }
+    }
+}
 
 pub enum PestGeneType {
     AttackRate,
@@ -15,6 +57,7 @@ pub enum PestGeneType {
     DmgToCorn,
     DmgToPotato,
     DmgToLettuce,
+    PesticideResistance,
 }
 
 struct PestGene {
@@ -33,40 +76,70 @@ impl PestGene {
 
 pub struct Pest {
     pest_genes: Vec<PestGene>,
+    /// How well the pest did on its last night out: how much it ate,
+    /// or 0 if it starved or the crop it attacked killed it
     fitness: f32,
+    stage: Stage,
+    /// Nights spent in the current stage
+    age: u32,
 }
 
 impl Pest {
     pub fn new() -> Pest {
-        let normal = Normal::new(MEAN, STD_DEV).unwrap();
-        let almost_normal = Normal::new(0.2, STD_DEV).unwrap();
-        let attack_rate = almost_normal
-            .sample(&mut rand::thread_rng())
-            .clamp(0.0, 1.0);
-        let breed_speed = normal.sample(&mut rand::thread_rng()).clamp(0.0, 1.0);
-        let carrot_dmg = normal.sample(&mut rand::thread_rng()).clamp(0.0, 1.0);
-        let corn_dmg = normal.sample(&mut rand::thread_rng()).clamp(0.0, 1.0);
-        let potato_dmg = normal.sample(&mut rand::thread_rng()).clamp(0.0, 1.0);
-        let lettuce_dmg = normal.sample(&mut rand::thread_rng()).clamp(0.0, 1.0);
-        let fitness_value =
-            (attack_rate + breed_speed + carrot_dmg + corn_dmg + potato_dmg + lettuce_dmg) / 6.0;
-        Pest {
-            pest_genes: vec![
-                PestGene::new(PestGeneType::AttackRate, attack_rate),
-                PestGene::new(PestGeneType::BreedSpeed, breed_speed),
-                PestGene::new(PestGeneType::DmgToCarrot, carrot_dmg),
-                PestGene::new(PestGeneType::DmgToCorn, corn_dmg),
-                PestGene::new(PestGeneType::DmgToPotato, potato_dmg),
-                PestGene::new(PestGeneType::DmgToLettuce, lettuce_dmg),
-            ],
-            fitness: fitness_value,
-        }
+        Pest::from_means(&BASE_MEANS)
+    }
+
+    /*
+       Makes a new adult pest with each gene drawn around the given mean,
+       in the order `make_pest` takes them
+    */
+    pub fn from_means(means: &[f32; NUM_GENES]) -> Pest {
+        let mut v: Vec<f32> = means
+            .iter()
+            .map(|m| {
+                Normal::new(*m, STD_DEV)
+                    .unwrap()
+                    .sample(&mut rng::rng())
+                    .clamp(0.0, 1.0)
+            })
+            .collect();
+        // Hasn't been out to eat yet
+        v.push(0.0);
+        Pest::make_pest(v)
     }
 
     pub fn get_fitness(&self) -> f32 {
         self.fitness
     }
 
+    pub fn get_stage(&self) -> Stage {
+        self.stage
+    }
+
+    /// The same pest at the start of the given stage, e.g. an egg it lays
+    pub fn with_stage(mut self, stage: Stage) -> Pest {
+        self.stage = stage;
+        self.age = 0;
+        self
+    }
+
+    /*
+       The pest gets a night older. Eggs hatch and larvae grow up once
+       they've spent long enough in their stage. Returns true if the
+       pest moved on to its next stage
+    */
+    pub fn develop(&mut self) -> bool {
+        self.age += 1;
+        let next = match self.stage {
+            Stage::Egg if self.age >= EGG_NIGHTS => Stage::Larva,
+            Stage::Larva if self.age >= LARVA_NIGHTS => Stage::Adult,
+            _ => return false,
+        };
+        self.stage = next;
+        self.age = 0;
+        true
+    }
+
     /*
        Makes a pest with a f32 vector
        Vector must be arranged in the correct order
@@ -76,7 +149,9 @@ impl Pest {
        vector[3]: DmgToCorn
        vector[4]: DmgToPotato
        vector[5]: DmgToLettuce
-       vector[6]: fitness
+       vector[6]: PesticideResistance
+       vector[7]: fitness
+       The pest is a new adult
     */
     pub fn make_pest(v: Vec<f32>) -> Pest {
         Pest {
@@ -87,8 +162,11 @@ impl Pest {
                 PestGene::new(PestGeneType::DmgToCorn, *v.get(3).unwrap()),
                 PestGene::new(PestGeneType::DmgToPotato, *v.get(4).unwrap()),
                 PestGene::new(PestGeneType::DmgToLettuce, *v.get(5).unwrap()),
+                PestGene::new(PestGeneType::PesticideResistance, *v.get(6).unwrap()),
             ],
-            fitness: *v.get(6).unwrap(),
+            fitness: *v.get(7).unwrap(),
+            stage: Stage::Adult,
+            age: 0,
         }
     }
 
@@ -97,22 +175,20 @@ impl Pest {
        Genes are picked entirely randomly from the two parents
        Should be called on parent 1 with parent 2 as an argument
        To turn the f32 vector into a new pest call make_pest()
+       The child's fitness starts at 0 until it forages
     */
     pub fn breed_pests(&self, p: &Pest) -> Vec<f32> {
         let mut temp = Vec::new();
-        let mut rng = rand::thread_rng();
-        let mut sum = 0.0;
-        for i in 0..6 {
+        let mut rng = rng::rng();
+        for i in 0..NUM_GENES {
             let r: i32 = rng.gen_range(0..2);
             if r == 0 {
                 temp.push(self.pest_genes.get(i).unwrap().value);
-                sum += self.pest_genes.get(i).unwrap().value;
             } else {
                 temp.push(p.pest_genes.get(i).unwrap().value);
-                sum += self.pest_genes.get(i).unwrap().value;
             }
         }
-        temp.push(sum / 6.0);
+        temp.push(0.0);
 
         temp
     }
@@ -121,30 +197,44 @@ impl Pest {
        Goes through the pests genes and mutating them at random
     */
     pub fn mutate_pest(&mut self) {
-        let mut rng = rand::thread_rng();
+        let mut rng = rng::rng();
 
-        for i in 0..6 {
+        for i in 0..NUM_GENES {
             let r: f32 = rng.gen_range(0.0..1.0);
             if r < MUTATE_RATE {
                 let normal = Normal::new(self.pest_genes[i].value, STD_DEV).unwrap();
-                self.pest_genes[i].value = normal.sample(&mut rand::thread_rng()).clamp(0.0, 1.0);
-                self.recalc_fitness();
+                self.pest_genes[i].value = normal.sample(&mut rng::rng()).clamp(0.0, 1.0);
             }
         }
     }
 
     /*
-       Just recalculates a pests fitness incase of a mutation
+       Sends the pest out to attack a crop of type t with the given pest
+       resistance. Its fitness becomes the damage it did, scaled by how much
+       its species likes the crop, or 0 if the crop's resistance killed it.
+       Returns true if the attack succeeded
     */
-    pub fn recalc_fitness(&mut self) {
-        let attack_rate = self.pest_genes[0].value;
-        let breed_speed = self.pest_genes[1].value;
-        let carrot_dmg = self.pest_genes[2].value;
-        let corn_dmg = self.pest_genes[3].value;
-        let potato_dmg = self.pest_genes[4].value;
-        let lettuce_dmg = self.pest_genes[5].value;
-        self.fitness =
-            (attack_rate + breed_speed + carrot_dmg + corn_dmg + potato_dmg + lettuce_dmg) / 6.0;
+    pub fn forage(&mut self, t: CropType, resistance: f32, preference: f32) -> bool {
+        let damage = self.damage_to(t) * preference;
+        if damage > resistance {
+            self.fitness = damage;
+            true
+        } else {
+            self.fitness = 0.0;
+            false
+        }
+    }
+
+    /*
+       A pest with nothing to eat
+    */
+    pub fn starve(&mut self) {
+        self.fitness = 0.0;
+    }
+
+    /// Every gene of the pest, in the order `make_pest` takes them
+    pub fn get_genes(&self) -> Vec<f32> {
+        self.pest_genes.iter().map(|g| g.value).collect()
     }
 
     /*
@@ -158,6 +248,7 @@ impl Pest {
             PestGeneType::DmgToCorn => self.pest_genes[3].value,
             PestGeneType::DmgToPotato => self.pest_genes[4].value,
             PestGeneType::DmgToLettuce => self.pest_genes[5].value,
+            PestGeneType::PesticideResistance => self.pest_genes[6].value,
         }
     }
 
@@ -170,14 +261,24 @@ impl Pest {
             s.push_str(((g.value).to_string() + ";").as_ref());
         }
         s.push_str((self.fitness.to_string()).as_ref());
+        s.push_str(&format!(";{};{}", self.stage, self.age));
         s.push('\n');
         s
     }
 
     /*
        Creates a new pest from a save string
+       Pests saved before pesticides existed get a new resistance gene,
+       and pests saved before lifecycle stages are adults
     */
     pub fn from_save_string(v: Vec<&str>) -> Pest {
+        if v.len() < NUM_GENES + 1 {
+            let mut old: Vec<f32> = v.iter().map(|g| g.parse::<f32>().unwrap()).collect();
+            let fitness = old.pop().unwrap();
+            old.push(Pest::new().get_pest_gene(PestGeneType::PesticideResistance));
+            old.push(fitness);
+            return Pest::make_pest(old);
+        }
         Pest {
             pest_genes: vec![
                 PestGene::new(PestGeneType::AttackRate, v[0].parse::<f32>().unwrap()),
@@ -186,15 +287,22 @@ impl Pest {
                 PestGene::new(PestGeneType::DmgToCorn, v[3].parse::<f32>().unwrap()),
                 PestGene::new(PestGeneType::DmgToPotato, v[4].parse::<f32>().unwrap()),
                 PestGene::new(PestGeneType::DmgToLettuce, v[5].parse::<f32>().unwrap()),
+                PestGene::new(
+                    PestGeneType::PesticideResistance,
+                    v[6].parse::<f32>().unwrap(),
+                ),
             ],
-            fitness: v[6].parse::<f32>().unwrap(),
+            fitness: v[7].parse::<f32>().unwrap(),
+            stage: v
+                .get(8)
+                .map_or(Stage::Adult, |s| s.parse::<Stage>().unwrap()),
+            age: v.get(9).map_or(0, |a| a.parse::<u32>().unwrap()),
         }
     }
 
-    /// Takes in a crop, returns the value of the corresponding attack gene
-    pub fn attack_crop(&self, c: &mut Crop) -> f32 {
+    /// Value of the attack gene for crops of type `a`
+    pub fn damage_to(&self, a: CropType) -> f32 {
         let v;
-        let a = c.get_crop_type_enum();
         match a {
             CropType::Carrot => v = self.pest_genes[2].value,
             CropType::Corn => v = self.pest_genes[3].value,
@@ -236,8 +344,14 @@ impl Pest {
                     PestGeneType::DmgToLettuce,
                     self.pest_genes.get(5).unwrap().value,
                 ),
+                PestGene::new(
+                    PestGeneType::PesticideResistance,
+                    self.pest_genes.get(6).unwrap().value,
+                ),
             ],
             fitness: self.fitness,
+            stage: self.stage,
+            age: self.age,
         }
     }
 }
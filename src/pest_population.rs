@@ -1,30 +1,105 @@
+use crate::crop::CropType;
+use crate::immigration;
+use crate::immigration::RegionalPool;
 use crate::pest;
-use crate::pest::Pest;
-use crate::pest::PestGeneType::AttackRate;
+use crate::pest::PestGeneType::{AttackRate, PesticideResistance};
+use crate::pest::{Pest, Stage};
+use crate::pest_species::PestSpecies;
+use crate::pest_stats::{GenerationStats, Summary};
+use crate::rng;
+use crate::selection::SelectionStrategy;
 use rand;
+use rand::seq::SliceRandom;
 use rand::Rng;
 use rand_distr::{Distribution, Normal};
 
 const MEAN: f32 = 0.5;
 const STD_DEV: f32 = 0.1;
-pub(crate) const POP_SIZE: usize = 500;
+/// Size of a species' population the first time it comes to the farm
+pub(crate) const START_POP_SIZE: usize = 500;
+/// Populations never grow past this size
+const MAX_POP_SIZE: usize = 2000;
+/// Pests the wild plants around the farm can feed, whatever is planted
+const WILD_CAPACITY: f32 = 50.0;
+/// Pests a fully grown crop the species likes can feed. Younger crops feed a quarter as many.
+const PESTS_PER_CROP: f32 = 25.0;
+/// Young that each pest that ate and survived raises
+const OFFSPRING_PER_SURVIVOR: f32 = 2.0;
+/// Share of a population that survives a night with nothing to eat
+const STARVATION_SURVIVAL: f32 = 0.5;
+/// Share of a dormant population that survives each night out of season...
+const DORMANT_SURVIVAL: f32 = 0.97;
+/// ...and each winter night, apart from eggs, which sit out the winter
+/// in the soil as if it were any other season
+const WINTER_SURVIVAL: f32 = 0.85;
+/// Chance each night in season that an extinct species moves back in from outside the farm,
+/// in place of the usual immigration
+const RECOLONIZE_CHANCE: f32 = 0.1;
+/// Number of pests that recolonize the farm
+const RECOLONIZERS: usize = 20;
+/// Pesticide pressure added each time the population's pests meet a sprayed crop
+const PRESSURE_PER_EXPOSURE: f32 = 0.05;
+/// Share of the pesticide pressure left after each generation
+const PRESSURE_DECAY: f32 = 0.8;
+
+/// A crop pests can feed on
+#[derive(Copy, Clone)]
+pub struct Host {
+    pub t: CropType,
+    pub resistance: f32,
+    /// Fully grown crops feed more pests
+    pub mature: bool,
+}
 
 pub struct PestPopulation {
     pest_population: Vec<Pest>,
     avg_attack_chance: f32,
+    /// The kind of pest this population is made of
+    species: PestSpecies,
+    /// How much pesticide the population has run into lately, from 0 to 1.
+    /// The higher it is, the more resistant pests are favored when breeding.
+    pesticide_pressure: f32,
+    /// Average pesticide resistance after each generation, oldest first
+    resistance_history: Vec<f32>,
+    /// Successful attacks last night on carrots, corn, potatoes and lettuce
+    successes: [u32; 4],
+    /// Feeding pests killed by crop pest resistance last night
+    deaths: u32,
+    /// Crops the population's colonies fed on last night, each with the
+    /// pest resistance the colony had to beat
+    attacked: Vec<Host>,
+    /// How parents are picked for the next generation
+    selection: Box<dyn SelectionStrategy>,
+    /// The species' pests outside the farm, where immigrants come from
+    pool: RegionalPool,
+    /// Statistics of each generation, oldest first
+    stats: Vec<GenerationStats>,
 }
 
 impl PestPopulation {
-    pub fn new() -> PestPopulation {
+    pub fn new(species: PestSpecies, selection: Box<dyn SelectionStrategy>) -> PestPopulation {
         let mut temp = Vec::new();
         let mut z = 0.0;
 
         PestPopulation {
             pest_population: temp,
             avg_attack_chance: z,
+            species,
+            pesticide_pressure: 0.0,
+            resistance_history: Vec::new(),
+            successes: [0; 4],
+            deaths: 0,
+            attacked: Vec::new(),
+            selection,
+            pool: RegionalPool::new(),
+            stats: Vec::new(),
         }
     }
 
+    pub fn get_species(&self) -> &PestSpecies {
+        &self.species
+    }
+
     pub fn get_length(&self) -> usize {
         self.pest_population.len()
     }
@@ -37,24 +112,249 @@ impl PestPopulation {
         self.avg_attack_chance
     }
 
+    /// Average pesticide resistance of the population right now
+    pub fn get_avg_resistance(&self) -> f32 {
+        let sum: f32 = self
+            .pest_population
+            .iter()
+            .map(|p| p.get_pest_gene(PesticideResistance))
+            .sum();
+        sum / self.pest_population.len().max(1) as f32
+    }
+
+    pub fn get_resistance_history(&self) -> &Vec<f32> {
+        &self.resistance_history
+    }
+
+    pub fn set_resistance_history(&mut self, history: Vec<f32>) {
+        self.resistance_history = history;
+    }
+
+    pub fn get_pool(&self) -> &RegionalPool {
+        &self.pool
+    }
+
+    pub fn set_pool(&mut self, pool: RegionalPool) {
+        self.pool = pool;
+    }
+
+    pub fn get_stats(&self) -> &Vec<GenerationStats> {
+        &self.stats
+    }
+
+    pub fn set_stats(&mut self, stats: Vec<GenerationStats>) {
+        self.stats = stats;
+    }
+
+    /// Successful attacks last night on crops of type `t`
+    pub fn get_successes(&self, t: CropType) -> u32 {
+        match t {
+            CropType::None => 0,
+            CropType::Carrot => self.successes[0],
+            CropType::Corn => self.successes[1],
+            CropType::Potato => self.successes[2],
+            CropType::Lettuce => self.successes[3],
+        }
+    }
+
+    /// Pests killed by crop pest resistance last night
+    pub fn get_deaths(&self) -> u32 {
+        self.deaths
+    }
+
+    /// Forget last night's attacks before the colonies feed again
+    pub fn clear_attacks(&mut self) {
+        self.successes = [0; 4];
+        self.deaths = 0;
+        self.attacked.clear();
+    }
+
+    /// One of the population's colonies attacked `host`, whose pest
+    /// resistance is what the colony had to beat, and either fed or
+    /// lost a pest to the crop
+    pub fn record_attack(&mut self, host: Host, success: bool) {
+        if success {
+            let i = match host.t {
+                CropType::Carrot => 0,
+                CropType::Corn => 1,
+                CropType::Potato => 2,
+                _ => 3,
+            };
+            self.successes[i] += 1;
+        } else {
+            self.deaths += 1;
+        }
+        self.attacked.push(host);
+    }
+
+    /// Returns true if any of the population's colonies fed last night
+    pub fn fed_tonight(&self) -> bool {
+        !self.attacked.is_empty()
+    }
+
+    /// Returns true if any of the population's pests are in a stage that feeds
+    pub fn has_feeders(&self) -> bool {
+        self.pest_population
+            .iter()
+            .any(|p| self.species.attacks_as(p.get_stage()))
+    }
+
+    /*
+       Every pest in a stage that feeds takes its share of one of the
+       attacks the colonies made tonight, picked at random. How it would
+       have done against that crop sets its fitness for the next
+       generation, so pests adapt to what the colonies actually ran into.
+       With no attacks every feeder starves
+    */
+    pub fn forage(&mut self) {
+        let mut rng = rng::rng();
+        for pest in self.pest_population.iter_mut() {
+            if !self.species.attacks_as(pest.get_stage()) {
+                continue;
+            }
+            if self.attacked.is_empty() {
+                pest.starve();
+                continue;
+            }
+            let host = self.attacked[rng.gen_range(0..self.attacked.len())];
+            pest.forage(host.t, host.resistance, self.species.preference(host.t));
+        }
+    }
+
+    /// Some of the population's pests ran into a sprayed crop
+    pub fn record_exposure(&mut self) {
+        self.pesticide_pressure = (self.pesticide_pressure + PRESSURE_PER_EXPOSURE).min(1.0);
+    }
+
     /*
-       If the pest population is under the appropriate size it will fill it up with new pests
+       Fills a brand new population up to its starting size with new pests
+       in the stage the species arrives in
     */
     pub fn fill_pest_population(&mut self) {
-        while self.pest_population.len() < POP_SIZE {
-            self.pest_population.push(pest::Pest::new());
+        let stage = self.species.arrival_stage();
+        while self.pest_population.len() < START_POP_SIZE {
+            self.pest_population
+                .push(pest::Pest::new().with_stage(stage));
         }
     }
 
     /*
-       Recalculates average attack chance for when pests die or when a new generation is added
+       Recalculates average attack chance for when pests die or when a new generation is added.
+       An extinct population never attacks
     */
     pub fn find_avg_attack_chance(&mut self) {
         let mut sum = 0.0;
-        for g in 0..POP_SIZE {
+        for g in 0..self.pest_population.len() {
             sum = sum + self.pest_population[g].get_pest_gene(AttackRate);
         }
-        self.avg_attack_chance = sum / POP_SIZE as f32;
+        self.avg_attack_chance = sum / self.pest_population.len().max(1) as f32;
+    }
+
+    pub fn is_extinct(&self) -> bool {
+        self.pest_population.is_empty()
+    }
+
+    /*
+       Number of pests the food on the farm can support, counting how much
+       the species likes each crop
+    */
+    pub fn carrying_capacity(&self, hosts: &[Host]) -> usize {
+        let food: f32 = hosts
+            .iter()
+            .map(|h| self.species.preference(h.t) * if h.mature { 1.0 } else { 0.25 })
+            .sum();
+        ((WILD_CAPACITY + food * PESTS_PER_CROP) as usize).min(MAX_POP_SIZE)
+    }
+
+    /*
+       Kills off a random share of the population, keeping survival of it
+    */
+    fn shrink(&mut self, survival: f32) {
+        let keep = (self.pest_population.len() as f32 * survival).floor() as usize;
+        self.pest_population.shuffle(&mut rng::rng());
+        self.pest_population.truncate(keep);
+        self.find_avg_attack_chance();
+    }
+
+    /*
+       Eggs hatch and larvae grow up
+    */
+    pub fn develop(&mut self) {
+        for pest in self.pest_population.iter_mut() {
+            pest.develop();
+        }
+    }
+
+    /*
+       Out of season the species lies dormant, and some pests don't make it.
+       Winter is the hardest on everything but eggs
+    */
+    pub fn go_dormant(&mut self, winter: bool) {
+        let mut rng = rng::rng();
+        self.pest_population.retain(|p| {
+            let survival = if winter && p.get_stage() != Stage::Egg {
+                WINTER_SURVIVAL
+            } else {
+                DORMANT_SURVIVAL
+            };
+            rng.gen::<f32>() < survival
+        });
+        self.find_avg_attack_chance();
+    }
+
+    /*
+       The regional pool's genes drift a little, in season or not
+    */
+    pub fn drift_pool(&mut self) {
+        self.pool.drift();
+    }
+
+    /*
+       Pests from the regional pool may wander onto the farm, in the stage
+       the species arrives in. An extinct
+       species needs a bigger group to move back in, and does so less
+       often. Returns the number of pests that arrived
+    */
+    pub fn immigrate(&mut self) -> usize {
+        let mut rng = rng::rng();
+        let arrivals = if self.is_extinct() {
+            if rng.gen::<f32>() >= RECOLONIZE_CHANCE {
+                return 0;
+            }
+            RECOLONIZERS
+        } else {
+            if rng.gen::<f32>() >= immigration::IMMIGRATION_CHANCE {
+                return 0;
+            }
+            rng.gen_range(1..=immigration::MAX_IMMIGRANTS)
+        };
+        for _ in 0..arrivals {
+            let stage = self.species.arrival_stage();
+            self.pest_population
+                .push(self.pool.immigrant().with_stage(stage));
+        }
+        self.find_avg_attack_chance();
+        arrivals
+    }
+
+    /*
+       Sticky traps catch a share of the adults
+    */
+    pub fn kill_adults(&mut self, share: f32) {
+        let mut rng = rng::rng();
+        self.pest_population
+            .retain(|p| p.get_stage() != Stage::Adult || rng.gen::<f32>() >= share);
+        self.find_avg_attack_chance();
+    }
+
+    /*
+       Predators eat n random pests
+    */
+    pub fn remove_random(&mut self, n: usize) {
+        let keep = self.pest_population.len().saturating_sub(n);
+        self.pest_population.shuffle(&mut rng::rng());
+        self.pest_population.truncate(keep);
+        self.find_avg_attack_chance();
     }
 
     pub fn add_pest(&mut self, p: Pest) {
@@ -62,36 +362,149 @@ impl PestPopulation {
     }
 
     /*
-       Generates a new generation
+       Generates a new generation. Eggs and larvae carry over to the next
+       night, except feeding larvae a crop killed or that found nothing to
+       eat. The selection strategy picks parents from the adults by each
+       one's fitness, weighted by how likely it is to survive pesticide.
+       Every adult that ate and survived lays eggs, up to what the farm's
+       food can support (capacity), then dies; with nothing to eat the
+       adults starve. The new generation's statistics are recorded
     */
-    pub fn next_generation(&mut self) {
-        let mut rng = rand::thread_rng();
-        let mut temp = Vec::new();
-        //print!("outside loop");
-        for g in 0..POP_SIZE {
-            let n = (self.pest_population[g].get_fitness() * 100.0).round();
-            //print!("{}", n);
-            for i in 0..n as i32 {
-                //print!("loop1");
-                let test_pest = self.pest_population[g].clone();
-                temp.push(test_pest);
+    pub fn next_generation(&mut self, capacity: usize) {
+        let larvae_feed = self.species.attacks_as(Stage::Larva);
+        let mut young = Vec::new();
+        let mut adults = Vec::new();
+        for p in self.pest_population.drain(..) {
+            match p.get_stage() {
+                Stage::Adult => adults.push(p),
+                Stage::Larva if larvae_feed && p.get_fitness() == 0.0 => {}
+                _ => young.push(p),
             }
         }
-        //print!("{}", temp.len());
+        self.pest_population = adults;
+        let fitness = Summary::of(
+            &self
+                .pest_population
+                .iter()
+                .map(|p| p.get_fitness())
+                .collect::<Vec<f32>>(),
+        );
+
+        let weights: Vec<f32> = self
+            .pest_population
+            .iter()
+            .map(|p| {
+                // Under pesticide pressure only resistant pests live long enough to breed
+                let resistance = p.get_pest_gene(PesticideResistance);
+                let survival = 1.0 - self.pesticide_pressure * (1.0 - resistance);
+                (p.get_fitness() * survival).max(0.0)
+            })
+            .collect();
+        // No adult ate and survived, e.g. nothing was planted
+        if weights.iter().all(|w| *w == 0.0) {
+            self.shrink(STARVATION_SURVIVAL);
+            self.pest_population.append(&mut young);
+            self.find_avg_attack_chance();
+            self.stats
+                .push(GenerationStats::new(&self.pest_population, fitness));
+            return;
+        }
+
+        let survivors: f32 = self
+            .pest_population
+            .iter()
+            .filter(|p| p.get_fitness() > 0.0)
+            .map(|p| {
+                let resistance = p.get_pest_gene(PesticideResistance);
+                1.0 - self.pesticide_pressure * (1.0 - resistance)
+            })
+            .sum();
+        let size = ((survivors * OFFSPRING_PER_SURVIVOR).round() as usize)
+            .min(capacity.saturating_sub(young.len()));
+
+        // The best pests may carry over unchanged
+        let elites = self
+            .selection
+            .elites()
+            .min(size)
+            .min(self.pest_population.len());
+        let mut order: Vec<usize> = (0..self.pest_population.len()).collect();
+        order.sort_by(|a, b| weights[*b].partial_cmp(&weights[*a]).unwrap());
+        let mut next: Vec<Pest> = order[..elites]
+            .iter()
+            .map(|i| self.pest_population[*i].clone())
+            .collect();
 
-        for i in 0..POP_SIZE {
-            let pest0: &Pest = temp.get(rng.gen_range(0..temp.len())).unwrap();
-            let pest1: &Pest = temp.get(rng.gen_range(0..temp.len())).unwrap();
-            let mut pest3 = Pest::make_pest(pest0.breed_pests(pest1));
-            pest3.recalc_fitness();
+        let parents = self.selection.select(&weights, 2 * (size - elites));
+        for pair in parents.chunks(2) {
+            let pest0 = &self.pest_population[pair[0]];
+            let pest1 = &self.pest_population[pair[1]];
+            let mut pest3 = Pest::make_pest(pest0.breed_pests(pest1)).with_stage(Stage::Egg);
             pest3.mutate_pest();
-            self.pest_population[i] = pest3;
+            next.push(pest3);
         }
+        next.append(&mut young);
+        self.pest_population = next;
 
         self.find_avg_attack_chance();
+        self.pesticide_pressure *= PRESSURE_DECAY;
+        self.resistance_history.push(self.get_avg_resistance());
+        self.stats
+            .push(GenerationStats::new(&self.pest_population, fitness));
     }
 
     pub fn kill_pest(&mut self, i: usize) {
         self.pest_population.remove(i);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::selection::Roulette;
+
+    /// A night on the farm, with a colony of a random feeding pest on
+    /// every crop, as in pest_sim
+    fn night(pest_pop: &mut PestPopulation, hosts: &[Host]) {
+        pest_pop.develop();
+        pest_pop.clear_attacks();
+        let feeders: Vec<usize> = (0..pest_pop.get_length())
+            .filter(|i| {
+                pest_pop
+                    .species
+                    .attacks_as(pest_pop.get_pest(*i).get_stage())
+            })
+            .collect();
+        if !feeders.is_empty() {
+            for host in hosts {
+                let pest = pest_pop.get_pest(feeders[rng::rng().gen_range(0..feeders.len())]);
+                let attack = pest.damage_to(host.t) * pest_pop.species.preference(host.t);
+                pest_pop.record_attack(*host, attack > host.resistance);
+            }
+        }
+        pest_pop.forage();
+        pest_pop.next_generation(pest_pop.carrying_capacity(hosts));
+    }
+
+    #[test]
+    fn larva_only_species_keeps_going_on_undefended_field() {
+        rng::seed(3);
+        let line = "species;RootWorm;Spring,Fall;2;Burrow;1.4;0.4;1.4;0.3;Larva";
+        let species = PestSpecies::from_line(&line.split(";").collect::<Vec<&str>>());
+        let mut pest_pop = PestPopulation::new(species, Box::new(Roulette));
+        pest_pop.fill_pest_population();
+        let hosts = vec![
+            Host {
+                t: CropType::Potato,
+                resistance: 0.0,
+                mature: true,
+            };
+            20
+        ];
+        for _ in 0..100 {
+            night(&mut pest_pop, &hosts);
+        }
+        assert!(!pest_pop.is_extinct());
+        assert!(pest_pop.get_stats().len() > 20);
+    }
+}
@@ -0,0 +1,145 @@
+//! Module for the kinds of pests that visit the farm. Species are
+//! read from `saves/pest_species.txt` so new ones can be added
+//! without touching the code.
+
+use std::fs::File;
+use std::io::Read;
+use std::str::FromStr;
+
+use crate::calendar::Season;
+use crate::crop::CropType;
+use crate::pest::Stage;
+
+/// File the pest species are defined in
+pub const SPECIES_FILE: &str = "saves/pest_species.txt";
+
+/// How a species' colonies behave when they attack at night
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum Behavior {
+    /// Weak but quick to move; colonies spread as soon as they land
+    Swarm,
+    /// Eats the crop from the outside, as pests always have
+    Chew,
+    /// Attacks the roots, where the crop's pest resistance does half as
+    /// much, but spreads slowly underground
+    Burrow,
+    /// Birds: peck at crops and fly off without laying on the farm.
+    /// Scarecrows keep them away.
+    Peck,
+}
+
+impl FromStr for Behavior {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "Swarm" => Ok(Behavior::Swarm),
+            "Chew" => Ok(Behavior::Chew),
+            "Burrow" => Ok(Behavior::Burrow),
+            "Peck" => Ok(Behavior::Peck),
+            _ => Err(format!("invalid Behavior: {}", s)),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct PestSpecies {
+    name: String,
+    /// Seasons the species lands on the farm in
+    seasons: Vec<Season>,
+    /// Number of colonies that try to land on a bug night
+    arrivals: usize,
+    behavior: Behavior,
+    /// How much the species likes carrots, corn, potatoes and lettuce,
+    /// in that order. Multiplies the damage it does to each.
+    preferences: [f32; 4],
+    /// Stages of the species' life that feed on crops
+    attack_stages: Vec<Stage>,
+}
+
+impl PestSpecies {
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn get_arrivals(&self) -> usize {
+        self.arrivals
+    }
+
+    pub fn get_behavior(&self) -> Behavior {
+        self.behavior
+    }
+
+    pub fn spawns_in(&self, season: Season) -> bool {
+        self.seasons.contains(&season)
+    }
+
+    /// Returns true if pests of this species feed on crops at `stage`
+    pub fn attacks_as(&self, stage: Stage) -> bool {
+        self.attack_stages.contains(&stage)
+    }
+
+    /// Stage the species' pests are in when they first come to the farm.
+    /// Species whose adults don't feed come as larvae, so they can eat
+    /// before they grow up and breed.
+    pub fn arrival_stage(&self) -> Stage {
+        if self.attacks_as(Stage::Adult) {
+            Stage::Adult
+        } else {
+            Stage::Larva
+        }
+    }
+
+    /// Damage multiplier against crops of type `t`
+    pub fn preference(&self, t: CropType) -> f32 {
+        match t {
+            CropType::None => 0.0,
+            CropType::Carrot => self.preferences[0],
+            CropType::Corn => self.preferences[1],
+            CropType::Potato => self.preferences[2],
+            CropType::Lettuce => self.preferences[3],
+        }
+    }
+
+    /// Parse a
+    /// `species;<name>;<seasons>;<arrivals>;<behavior>;<carrot>;<corn>;<potato>;<lettuce>;<stages>`
+    /// line, already split on `;`. Seasons and stages are separated by
+    /// commas. Species with no stages listed attack as adults.
+    pub(crate) fn from_line(v: &[&str]) -> PestSpecies {
+        PestSpecies {
+            name: v[1].to_string(),
+            seasons: v[2]
+                .split(",")
+                .map(|s| s.parse::<Season>().unwrap())
+                .collect(),
+            arrivals: v[3].parse::<usize>().unwrap(),
+            behavior: v[4].parse::<Behavior>().unwrap(),
+            preferences: [
+                v[5].parse::<f32>().unwrap(),
+                v[6].parse::<f32>().unwrap(),
+                v[7].parse::<f32>().unwrap(),
+                v[8].parse::<f32>().unwrap(),
+            ],
+            attack_stages: v.get(9).map_or(vec![Stage::Adult], |s| {
+                s.split(",").map(|s| s.parse::<Stage>().unwrap()).collect()
+            }),
+        }
+    }
+}
+
+/// Read every species from the file at `path`. Blank lines and lines
+/// starting with `#` are ignored.
+pub fn load_all(path: &str) -> Vec<PestSpecies> {
+    let mut file = File::open(path).expect("Can't open pest species file");
+    let mut contents = String::new();
+    file.read_to_string(&mut contents)
+        .expect("Can't read pest species file");
+    let mut species = Vec::new();
+    for line in contents.lines() {
+        let results: Vec<&str> = line.trim().split(";").collect();
+        if results[0] == "species" {
+            species.push(PestSpecies::from_line(&results));
+        }
+    }
+    species
+}
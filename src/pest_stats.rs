@@ -0,0 +1,168 @@
+//! Module for statistics on how each pest population evolves. After
+//! every generation the population records the spread of each of its
+//! pests' genes, how well its adults ate and how big it is. The history
+//! is saved to `pest_stats.csv` for designers to dig through and drawn
+//! on the graph screen in `stats_screen`, to show whether pests are
+//! adapting to what is being planted.
+
+use crate::pest;
+use crate::pest::Pest;
+
+/// File the history of every population is saved to
+pub const STATS_FILE: &str = "saves/pest_stats.csv";
+/// Short name of each gene, in the order `Pest::make_pest` takes them
+pub const GENE_NAMES: [&str; pest::NUM_GENES] = [
+    "AttackRate",
+    "BreedSpeed",
+    "DmgToCarrot",
+    "DmgToCorn",
+    "DmgToPotato",
+    "DmgToLettuce",
+    "PesticideResistance",
+];
+
+/// Mean, variance and range of a set of values
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct Summary {
+    pub mean: f32,
+    pub variance: f32,
+    pub min: f32,
+    pub max: f32,
+}
+
+impl Summary {
+    /// Summarize `values`. All zeros if there are none.
+    pub fn of(values: &[f32]) -> Summary {
+        if values.is_empty() {
+            return Summary {
+                mean: 0.0,
+                variance: 0.0,
+                min: 0.0,
+                max: 0.0,
+            };
+        }
+        let n = values.len() as f32;
+        let mean = values.iter().sum::<f32>() / n;
+        Summary {
+            mean,
+            variance: values.iter().map(|v| (v - mean).powi(2)).sum::<f32>() / n,
+            min: values.iter().cloned().fold(f32::INFINITY, f32::min),
+            max: values.iter().cloned().fold(f32::NEG_INFINITY, f32::max),
+        }
+    }
+
+    fn to_csv(&self) -> String {
+        format!("{},{},{},{}", self.mean, self.variance, self.min, self.max)
+    }
+
+    fn from_csv(v: &[&str]) -> Option<Summary> {
+        Some(Summary {
+            mean: v.first()?.parse().ok()?,
+            variance: v.get(1)?.parse().ok()?,
+            min: v.get(2)?.parse().ok()?,
+            max: v.get(3)?.parse().ok()?,
+        })
+    }
+}
+
+/// Statistics of one generation of a population
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct GenerationStats {
+    /// Pests in the population once the generation was bred
+    pub size: usize,
+    /// Fitness of the adults that bred it
+    pub fitness: Summary,
+    /// Each gene of the population's pests, in the order of `GENE_NAMES`
+    pub genes: [Summary; pest::NUM_GENES],
+}
+
+impl GenerationStats {
+    /// Statistics of `pests`, bred by adults whose fitness is summed up in `fitness`
+    pub fn new(pests: &[Pest], fitness: Summary) -> GenerationStats {
+        let values: Vec<Vec<f32>> = pests.iter().map(|p| p.get_genes()).collect();
+        let mut genes = [Summary::of(&[]); pest::NUM_GENES];
+        for (i, g) in genes.iter_mut().enumerate() {
+            *g = Summary::of(&values.iter().map(|v| v[i]).collect::<Vec<f32>>());
+        }
+        GenerationStats {
+            size: pests.len(),
+            fitness,
+            genes,
+        }
+    }
+
+    /// Header row of the stats file
+    pub fn csv_header() -> String {
+        let mut s = String::from("species,generation,size");
+        for name in std::iter::once("Fitness").chain(GENE_NAMES) {
+            s.push_str(&format!(",{0}Mean,{0}Variance,{0}Min,{0}Max", name));
+        }
+        s.push('\n');
+        s
+    }
+
+    /// A row of the stats file for generation `generation` of `species`
+    pub fn to_csv(&self, species: &str, generation: usize) -> String {
+        let mut s = format!("{},{},{}", species, generation, self.size);
+        s.push_str(&format!(",{}", self.fitness.to_csv()));
+        for g in &self.genes {
+            s.push_str(&format!(",{}", g.to_csv()));
+        }
+        s.push('\n');
+        s
+    }
+
+    /// Reads a row of the stats file, returning the species it belongs to.
+    /// Returns None for the header or a broken row.
+    pub fn from_csv(line: &str) -> Option<(String, GenerationStats)> {
+        let v: Vec<&str> = line.split(',').collect();
+        let size = v.get(2)?.parse::<usize>().ok()?;
+        let fitness = Summary::from_csv(v.get(3..7)?)?;
+        let mut genes = [fitness; pest::NUM_GENES];
+        for (i, g) in genes.iter_mut().enumerate() {
+            *g = Summary::from_csv(v.get(7 + 4 * i..11 + 4 * i)?)?;
+        }
+        Some((
+            v[0].to_string(),
+            GenerationStats {
+                size,
+                fitness,
+                genes,
+            },
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn summary_of_values() {
+        let s = Summary::of(&[1.0, 2.0, 3.0, 4.0]);
+        assert_eq!(s.mean, 2.5);
+        assert_eq!(s.variance, 1.25);
+        assert_eq!(s.min, 1.0);
+        assert_eq!(s.max, 4.0);
+    }
+
+    #[test]
+    fn summary_of_nothing_is_zero() {
+        assert_eq!(Summary::of(&[]).max, 0.0);
+    }
+
+    #[test]
+    fn csv_round_trips() {
+        let pests: Vec<Pest> = (0..10).map(|_| Pest::new()).collect();
+        let stats = GenerationStats::new(&pests, Summary::of(&[0.5, 0.25]));
+        let row = stats.to_csv("Aphid", 3);
+        let (species, loaded) = GenerationStats::from_csv(row.trim_end()).unwrap();
+        assert_eq!(species, "Aphid");
+        assert_eq!(loaded, stats);
+    }
+
+    #[test]
+    fn header_is_not_a_row() {
+        assert!(GenerationStats::from_csv(GenerationStats::csv_header().trim_end()).is_none());
+    }
+}
@@ -0,0 +1,97 @@
+//! Module for pesticide: a spray the player puts on tiles to kill the
+//! pests that attack the crops there. Pests that survive a spraying
+//! pass on their resistance, so leaning on it too hard breeds pests
+//! it no longer works on.
+
+use rand::Rng;
+use sdl2::image::LoadTexture;
+use sdl2::rect::Rect;
+use sdl2::render::{Texture, TextureCreator};
+use sdl2::video::WindowContext;
+
+use crate::crop::CropType;
+use crate::genes;
+use crate::pest_population::PestPopulation;
+use crate::population::Population;
+use crate::InventoryItemTrait;
+use crate::TILE_SIZE;
+
+/// Texture used in the inventory and the store
+pub const TEX_PATH: &str = "src/images/pesticide.png";
+/// Number of nights a spraying keeps working
+pub const SPRAY_NIGHTS: u32 = 3;
+
+/// A can of pesticide sitting in the player's inventory
+pub struct Pesticide<'a> {
+    texture: Texture<'a>,
+}
+
+impl<'a> Pesticide<'a> {
+    pub fn new(texture_creator: &'a TextureCreator<WindowContext>) -> Pesticide<'a> {
+        Pesticide {
+            texture: texture_creator.load_texture(TEX_PATH).unwrap(),
+        }
+    }
+}
+
+impl InventoryItemTrait for Pesticide<'_> {
+    fn get_value(&self) -> i32 {
+        1
+    }
+    fn texture(&self) -> &Texture {
+        &self.texture
+    }
+    fn src(&self) -> Rect {
+        Rect::new(0, 0, TILE_SIZE, TILE_SIZE)
+    }
+    /// Spray a tilled tile that isn't already sprayed. Returns the same
+    /// value as planting a seed so the can is used up.
+    fn inventory_input(
+        &self,
+        square: (i32, i32),
+        pop: &mut Population,
+    ) -> Option<(Option<CropType>, Option<genes::Genes>, Option<genes::Genes>)> {
+        let (x, y) = (square.0 as u32, square.1 as u32);
+        if !pop.get_tile_with_index(x, y).tilled() || pop.get_pesticide(x, y) > 0 {
+            return None;
+        }
+        pop.set_pesticide(x, y, SPRAY_NIGHTS);
+        Some((Some(CropType::None), None, None))
+    }
+    fn to_save_string(&self) -> Option<String> {
+        Some("pesticide\n".to_string())
+    }
+}
+
+/// A colony of `pest_pop`'s pests attacks a sprayed crop. Returns true
+/// if the pesticide kills it; the survivors count towards breeding
+/// resistance either way.
+pub fn kills(pest_pop: &mut PestPopulation, resistance: f32) -> bool {
+    pest_pop.record_exposure();
+    rand::thread_rng().gen::<f32>() >= resistance
+}
+
+/// Number of tiles the pesticide is still working on
+pub fn sprayed_tiles(pop: &Population) -> usize {
+    let mut n = 0;
+    for x in 0..pop.width() {
+        for y in 0..pop.height() {
+            if pop.get_pesticide(x, y) > 0 {
+                n += 1;
+            }
+        }
+    }
+    n
+}
+
+/// Sprayings lose a night of strength
+pub fn wear_off(pop: &mut Population) {
+    for x in 0..pop.width() {
+        for y in 0..pop.height() {
+            let nights = pop.get_pesticide(x, y);
+            if nights > 0 {
+                pop.set_pesticide(x, y, nights - 1);
+            }
+        }
+    }
+}
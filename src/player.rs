@@ -13,6 +13,7 @@ use sdl2::render::WindowCanvas;
 use sdl2::video::WindowContext;
 
 use crate::anim::Animation;
+use crate::config::Config;
 use crate::crop::Crop;
 use crate::crop::CropType;
 use crate::genes;
@@ -21,7 +22,7 @@ use crate::inventory::Inventory;
 use crate::population::Population;
 
 // Import constants from main
-use crate::{BG_H, BG_W, TILE_SIZE};
+use crate::TILE_SIZE;
 
 // Player sprites are 54x90 px.
 pub const PLAYER_WIDTH: u32 = 54;
@@ -31,8 +32,26 @@ pub const PLAYER_HEIGHT: u32 = 90;
 const PLAYER_EFF_HEIGHT_SKIP: i32 = 10;
 
 const SPEED_LIMIT: f32 = 5.0;
+/// Money the player starts the game with if the config doesn't say
+const STARTING_MONEY: i32 = 500;
 pub const ACCEL_RATE: f32 = 1.0;
 
+/// Money a new game starts with on the difficulty in the config. The
+/// `starting_money` setting pairs each difficulty with its money, e.g.
+/// `starting_money;Easy;1000;Normal;500`, and `difficulty` picks one.
+pub fn starting_money(config: &Config) -> i32 {
+    let difficulty = config
+        .get("difficulty")
+        .first()
+        .map_or("Normal", |d| d.as_str());
+    config
+        .get("starting_money")
+        .chunks(2)
+        .find(|pair| pair[0] == difficulty)
+        .and_then(|pair| pair.get(1)?.parse().ok())
+        .unwrap_or(STARTING_MONEY)
+}
+
 /// Sprite directions.
 pub enum Direction {
     Down,
@@ -61,6 +80,8 @@ pub struct Player<'a> {
     inventory: Inventory<'a>,
     /// is a tool being used
     tooluse: bool,
+    /// Player's money
+    money: i32,
 }
 
 impl<'a> Player<'a> {
@@ -105,6 +126,7 @@ impl<'a> Player<'a> {
             velocity: (0.0, 0.0),
             inventory,
             tooluse: false,
+            money: STARTING_MONEY,
         }
     }
 
@@ -327,6 +349,34 @@ impl<'a> Player<'a> {
             .set_y((self.pos.y() + vel.1).clamp(y_bounds.0, y_bounds.1));
     }
 
+    /// Move the player to (x, y) in map coordinates
+    pub fn set_position(&mut self, x: i32, y: i32) {
+        self.pos.set_x(x);
+        self.pos.set_y(y);
+        self.velocity = (0.0, 0.0);
+    }
+
+    pub fn get_money(&self) -> i32 {
+        self.money
+    }
+
+    pub fn set_money(&mut self, money: i32) {
+        self.money = money;
+    }
+
+    /// Give the player `amount` money, e.g. from a sale
+    pub fn earn(&mut self, amount: i32) {
+        self.money += amount.max(0);
+    }
+
+    /// Take up to `amount` money from the player. Returns how much
+    /// was actually taken.
+    pub fn charge(&mut self, amount: i32) -> i32 {
+        let paid = amount.clamp(0, self.money);
+        self.money -= paid;
+        paid
+    }
+
     pub fn tooluse(&self) -> bool {
         self.tooluse
     }
@@ -403,8 +453,9 @@ impl<'a> Player<'a> {
     }
 
     /// Returns the grid coordinates of the
-    /// tile the player is facing
-    pub fn get_facing(&self) -> (i32, i32) {
+    /// tile the player is facing, clamped to an
+    /// area of `bounds` (width, height) tiles
+    pub fn get_facing(&self, bounds: (u32, u32)) -> (i32, i32) {
         let offset: (i32, i32) = {
             match self.get_dir() {
                 // Down
@@ -421,9 +472,9 @@ impl<'a> Player<'a> {
         };
         let coordinates = (
             (((self.x() + TILE_SIZE as i32 / 2) / TILE_SIZE as i32) + offset.0)
-                .clamp(0, (BG_W / TILE_SIZE) as i32),
+                .clamp(0, bounds.0 as i32 - 1),
             (((self.y() + TILE_SIZE as i32) / TILE_SIZE as i32) + offset.1)
-                .clamp(0, (BG_H / TILE_SIZE) as i32),
+                .clamp(0, bounds.1 as i32 - 1),
         );
         coordinates
     }
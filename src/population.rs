@@ -1,17 +1,39 @@
-use crate::crop::Crop;
+use crate::colony::Colony;
+use crate::crop::{Crop, CropType};
+use crate::disease::Disease;
 use crate::genes;
+use crate::terrain::TileMap;
 use crate::tile::Tile;
-use crate::{BOTTOM_TILE_BOUND, RIGHT_TILE_BOUND, TILE_SIZE};
+use crate::TILE_SIZE;
+use sdl2::rect::Rect;
+use sdl2::render::Texture;
+
+/// Number of tiles a bought land plot adds to the farm
+pub const PLOT_SIZE: u32 = 8;
+/// The farm can't grow past this many tiles in either direction
+pub const MAX_AREA_SIZE: u32 = 62;
 
 //Struct used to combine tile and crop structs into one for easy storage into the vector
 pub struct CropTile<'a> {
     pub tile: Tile<'a>,
     pub crop: Crop<'a>,
+    /// Pests living on the tile, if any
+    pub colony: Option<Colony>,
+    /// Nights left before the tile's pesticide wears off
+    pub pesticide: u32,
+    /// Disease infecting the tile's crop, if any
+    pub disease: Option<Disease>,
 }
 
 impl<'a> CropTile<'a> {
     pub fn new(tile: Tile<'a>, crop: Crop<'a>) -> CropTile<'a> {
-        CropTile { tile, crop }
+        CropTile {
+            tile,
+            crop,
+            colony: None,
+            pesticide: 0,
+            disease: None,
+        }
     }
 
     pub fn set_crop(&mut self, c: Crop<'a>) {
@@ -37,6 +59,75 @@ impl<'a> Population<'a> {
         &mut self.crop_tile_vec
     }
 
+    /// Width of the area in tiles
+    pub fn width(&self) -> u32 {
+        self.crop_tile_vec.len() as u32
+    }
+
+    /// Height of the area in tiles
+    pub fn height(&self) -> u32 {
+        self.crop_tile_vec.first().map_or(0, |col| col.len() as u32)
+    }
+
+    /// Width of the area in pixels
+    pub fn pixel_width(&self) -> u32 {
+        self.width() * TILE_SIZE
+    }
+
+    /// Height of the area in pixels
+    pub fn pixel_height(&self) -> u32 {
+        self.height() * TILE_SIZE
+    }
+
+    /// Returns true if (x, y) is a tile index inside the area
+    pub fn in_bounds(&self, x: i32, y: i32) -> bool {
+        x >= 0 && y >= 0 && (x as u32) < self.width() && (y as u32) < self.height()
+    }
+
+    /// Resize the grid to `width` x `height` tiles. New tiles take their
+    /// terrain from `tile_map` and start empty; existing tiles keep their
+    /// crops. Shrinking drops the tiles past the new edge.
+    pub fn resize(
+        &mut self,
+        width: u32,
+        height: u32,
+        tile_map: &TileMap,
+        tile_texture: &'a Texture<'a>,
+        crop_texture: &'a Texture<'a>,
+        rotten_texture: &'a Texture<'a>,
+    ) {
+        self.crop_tile_vec.truncate(width as usize);
+        for col in self.crop_tile_vec.iter_mut() {
+            col.truncate(height as usize);
+        }
+        for x in 0..width as i32 {
+            if x as usize == self.crop_tile_vec.len() {
+                self.crop_tile_vec.push(Vec::new());
+            }
+            let col = &mut self.crop_tile_vec[x as usize];
+            for y in col.len() as i32..height as i32 {
+                let pos = Rect::new(
+                    (TILE_SIZE as i32) * x,
+                    (TILE_SIZE as i32) * y,
+                    TILE_SIZE,
+                    TILE_SIZE,
+                );
+                col.push(CropTile::new(
+                    Tile::new(pos, tile_texture, tile_map.get(x as usize, y as usize)),
+                    Crop::new(
+                        pos,
+                        0,
+                        crop_texture,
+                        rotten_texture,
+                        false,
+                        CropType::None,
+                        None,
+                    ),
+                ));
+            }
+        }
+    }
+
     //Lends out Tile struct at given x, y map coordinates
 
     pub fn get_tile(&self, x: i32, y: i32) -> &Tile {
@@ -72,6 +163,92 @@ impl<'a> Population<'a> {
         self.crop_tile_vec[x as usize][y as usize].crop = tar_crop;
     }
 
+    //Lends out the pest colony at given x, y index, if there is one
+    pub fn get_colony(&self, x: u32, y: u32) -> Option<&Colony> {
+        self.crop_tile_vec[x as usize][y as usize].colony.as_ref()
+    }
+
+    pub fn get_colony_mut(&mut self, x: u32, y: u32) -> Option<&mut Colony> {
+        self.crop_tile_vec[x as usize][y as usize].colony.as_mut()
+    }
+
+    pub fn set_colony(&mut self, x: u32, y: u32, colony: Option<Colony>) {
+        self.crop_tile_vec[x as usize][y as usize].colony = colony;
+    }
+
+    /// Nights left on the pesticide sprayed at given x, y index; 0 if unsprayed
+    pub fn get_pesticide(&self, x: u32, y: u32) -> u32 {
+        self.crop_tile_vec[x as usize][y as usize].pesticide
+    }
+
+    pub fn set_pesticide(&mut self, x: u32, y: u32, nights: u32) {
+        self.crop_tile_vec[x as usize][y as usize].pesticide = nights;
+    }
+
+    /// Disease infecting the crop at given x, y index, if any
+    pub fn get_disease(&self, x: u32, y: u32) -> Option<Disease> {
+        self.crop_tile_vec[x as usize][y as usize].disease
+    }
+
+    pub fn set_disease(&mut self, x: u32, y: u32, disease: Option<Disease>) {
+        self.crop_tile_vec[x as usize][y as usize].disease = disease;
+    }
+
+    /// Expand the area by one land plot along its shorter side, so the
+    /// farm stays roughly square. Returns false if the area is already
+    /// at its maximum size.
+    pub fn add_plot(
+        &mut self,
+        tile_map: &TileMap,
+        tile_texture: &'a Texture<'a>,
+        crop_texture: &'a Texture<'a>,
+        rotten_texture: &'a Texture<'a>,
+    ) -> bool {
+        let (mut width, mut height) = (self.width(), self.height());
+        if width <= height && width < MAX_AREA_SIZE {
+            width = (width + PLOT_SIZE).min(MAX_AREA_SIZE);
+        } else if height < MAX_AREA_SIZE {
+            height = (height + PLOT_SIZE).min(MAX_AREA_SIZE);
+        } else {
+            return false;
+        }
+        self.resize(
+            width,
+            height,
+            tile_map,
+            tile_texture,
+            crop_texture,
+            rotten_texture,
+        );
+        true
+    }
+
+    /// Number of land plots the area can still grow by
+    pub fn plots_left(&self) -> i32 {
+        let left = |side: u32| (MAX_AREA_SIZE.saturating_sub(side) + PLOT_SIZE - 1) / PLOT_SIZE;
+        (left(self.width()) + left(self.height())) as i32
+    }
+
+    /// Returns true if `r` (in map coordinates) overlaps any tile the
+    /// player can't walk over
+    pub fn collides(&self, r: Rect) -> bool {
+        let t = TILE_SIZE as i32;
+        let max_x = self.crop_tile_vec.len() as i32 - 1;
+        let max_y = self.crop_tile_vec[0].len() as i32 - 1;
+        for x in (r.left() / t).clamp(0, max_x)..=((r.right() - 1) / t).clamp(0, max_x) {
+            for y in (r.top() / t).clamp(0, max_y)..=((r.bottom() - 1) / t).clamp(0, max_y) {
+                if !self.crop_tile_vec[x as usize][y as usize]
+                    .tile
+                    .terrain()
+                    .walkable()
+                {
+                    return true;
+                }
+            }
+        }
+        false
+    }
+
     pub fn update_all_plants(&self) {}
 
     pub fn plant_seed(&self) {}
@@ -83,14 +260,16 @@ impl<'a> Population<'a> {
     pub fn get_neighbors(&self, x: i32, y: i32) -> Vec<(genes::Genes, f32)> {
         let mut v: Vec<&Crop> = Vec::new();
         // Loop through nearest rings
-        for col in (x - 2).clamp(0, RIGHT_TILE_BOUND)..(x + 2).clamp(0, RIGHT_TILE_BOUND) {
-            for row in (y - 2).clamp(0, BOTTOM_TILE_BOUND)..(y + 2).clamp(0, BOTTOM_TILE_BOUND) {
+        let right = self.width() as i32;
+        let bottom = self.height() as i32;
+        for col in (x - 2).clamp(0, right)..(x + 2).clamp(0, right) {
+            for row in (y - 2).clamp(0, bottom)..(y + 2).clamp(0, bottom) {
                 // Don't let a plant pollinate itself
                 if col == x && row == y {
                     continue;
                 }
                 let c = self.get_crop_with_index(col as u32, row as u32);
-                if c.get_crop_type_enum() != crate::crop::CropType::None && c.get_stage() == 3 {
+                if c.get_crop_type_enum() != CropType::None && c.get_stage() == 3 {
                     v.push(c);
                 }
             }
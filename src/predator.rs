@@ -0,0 +1,125 @@
+//! Module for the ladybugs that hunt the farm's pests: a biological
+//! alternative to spraying. Each night they eat pests in proportion to
+//! how many of both there are, and raise young on what they eat, so the
+//! two populations rise and fall after each other (Lotka-Volterra).
+//! Flower beds draw more of them in; pesticide kills them too.
+
+use rand::Rng;
+
+use crate::pest_population::PestPopulation;
+use crate::population::Population;
+
+/// Name used in messages and in `pest_data.txt`
+pub const NAME: &str = "Ladybug";
+/// Ladybugs living on the farm at the start of a new game
+const START_SIZE: f32 = 20.0;
+/// The population never grows past this size
+const MAX_SIZE: f32 = 1000.0;
+/// Share of a pest population each ladybug eats in a night
+const ATTACK_RATE: f32 = 0.001;
+/// New ladybugs raised for each pest eaten
+const CONVERSION: f32 = 0.2;
+/// Share of the ladybugs that die each night
+const DEATH_RATE: f32 = 0.1;
+/// Ladybugs each flower bed draws onto the farm every night
+const FLOWER_BOOST: f32 = 2.0;
+/// Share of the ladybugs killed by each sprayed tile
+const PESTICIDE_HARM: f32 = 0.03;
+/// Share of the ladybugs that survive each winter night
+const WINTER_SURVIVAL: f32 = 0.85;
+/// Colonies within this many tiles of a flower bed are hunted twice as hard
+const FLOWER_RANGE: i32 = 2;
+
+pub struct PredatorPopulation {
+    size: f32,
+    /// Pests eaten last night
+    eaten: usize,
+}
+
+impl PredatorPopulation {
+    pub fn new() -> PredatorPopulation {
+        PredatorPopulation {
+            size: START_SIZE,
+            eaten: 0,
+        }
+    }
+
+    /*
+       Nightly step. Pesticide on the farm kills some ladybugs first, then
+       the rest eat pests from every active pest population and raise
+       young on them. Flower beds keep drawing new ladybugs in, so a farm
+       with flowers never runs out of them for long
+    */
+    pub fn hunt(
+        &mut self,
+        pest_pops: &mut [PestPopulation],
+        active: &[bool],
+        flowers: usize,
+        sprayed: usize,
+    ) {
+        self.size *= (1.0 - PESTICIDE_HARM).powi(sprayed as i32);
+        self.eaten = 0;
+        for (pest_pop, active) in pest_pops.iter_mut().zip(active) {
+            if !active {
+                continue;
+            }
+            let n = pest_pop.get_length() as f32;
+            let eaten = (ATTACK_RATE * self.size * n).min(n).round() as usize;
+            pest_pop.remove_random(eaten);
+            self.eaten += eaten;
+        }
+        self.size +=
+            CONVERSION * self.eaten as f32 - DEATH_RATE * self.size + FLOWER_BOOST * flowers as f32;
+        self.size = self.size.clamp(0.0, MAX_SIZE);
+    }
+
+    /*
+       Ladybugs shelter through the winter and don't hunt
+    */
+    pub fn overwinter(&mut self) {
+        self.size *= WINTER_SURVIVAL;
+        self.eaten = 0;
+    }
+
+    /*
+       Ladybugs also pick off pests from the colonies on the farm. The
+       more ladybugs there are, the likelier each colony loses a pest,
+       and colonies near flower beds are hit twice as often
+    */
+    pub fn hunt_colonies(&self, pop: &mut Population, flower_beds: &[(i32, i32)]) {
+        let mut rng = rand::thread_rng();
+        let chance = self.size / MAX_SIZE;
+        for x in 0..pop.width() {
+            for y in 0..pop.height() {
+                if pop.get_colony(x, y).is_none() {
+                    continue;
+                }
+                let near_flowers = flower_beds.iter().any(|(fx, fy)| {
+                    (fx - x as i32).abs() <= FLOWER_RANGE && (fy - y as i32).abs() <= FLOWER_RANGE
+                });
+                let chance = if near_flowers { chance * 2.0 } else { chance };
+                if rng.gen::<f32>() < chance && pop.get_colony_mut(x, y).unwrap().lose_pest() {
+                    pop.set_colony(x, y, None);
+                }
+            }
+        }
+    }
+
+    pub fn to_save_string(&self) -> String {
+        format!("predator;{};{}\n", NAME, self.size)
+    }
+
+    /*
+       Reads a `predator;<name>;<size>` line. Returns None for any other
+       line
+    */
+    pub fn from_save_string(v: &[&str]) -> Option<PredatorPopulation> {
+        if v.len() < 3 || v[0] != "predator" || v[1] != NAME {
+            return None;
+        }
+        Some(PredatorPopulation {
+            size: v[2].parse::<f32>().ok()?,
+            eaten: 0,
+        })
+    }
+}
@@ -0,0 +1,175 @@
+//! Module for placeable items that protect the crops around them from
+//! pests during the nightly attack: scarecrows that scare off birds,
+//! sticky traps that catch adult pests and row covers that keep pests
+//! off the crops under them, at the cost of some pollination.
+
+use sdl2::image::LoadTexture;
+use sdl2::rect::Rect;
+use sdl2::render::{Texture, TextureCreator};
+use sdl2::video::WindowContext;
+use std::str::FromStr;
+
+use crate::crop::CropType;
+use crate::genes;
+use crate::item::Item;
+use crate::population::Population;
+use crate::InventoryItemTrait;
+use crate::TILE_SIZE;
+
+/// Chance each night that an adult colony near a sticky trap loses a pest
+pub const TRAP_CATCH: f32 = 0.5;
+/// Share of a population's adults each sticky trap on the farm kills a night...
+pub const TRAP_SHARE: f32 = 0.02;
+/// ...up to this share, however many traps there are
+pub const MAX_TRAP_SHARE: f32 = 0.3;
+/// Chance that a crop under a row cover gets its nightly try at pollination
+pub const COVERED_POLLINATION: f32 = 0.5;
+
+/// Kinds of pest protection
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum ProtectionKind {
+    Scarecrow,
+    StickyTrap,
+    RowCover,
+}
+
+impl ProtectionKind {
+    /// Path of the texture used both on the map and in the inventory
+    pub fn tex_path(&self) -> &'static str {
+        match self {
+            ProtectionKind::Scarecrow => "src/images/scarecrow.png",
+            ProtectionKind::StickyTrap => "src/images/sticky_trap.png",
+            ProtectionKind::RowCover => "src/images/row_cover.png",
+        }
+    }
+
+    /// Map an item texture path back to a kind of protection.
+    /// Returns None if the item is not a protection.
+    pub fn from_tex_path(path: &str) -> Option<ProtectionKind> {
+        match path {
+            "src/images/scarecrow.png" => Some(ProtectionKind::Scarecrow),
+            "src/images/sticky_trap.png" => Some(ProtectionKind::StickyTrap),
+            "src/images/row_cover.png" => Some(ProtectionKind::RowCover),
+            _ => None,
+        }
+    }
+
+    /// Tiles in each direction the protection reaches
+    /// * Scarecrow - a 7x7 square
+    /// * StickyTrap - a 5x5 square
+    /// * RowCover - the 3x3 square it is stretched over
+    pub fn radius(&self) -> i32 {
+        match self {
+            ProtectionKind::Scarecrow => 3,
+            ProtectionKind::StickyTrap => 2,
+            ProtectionKind::RowCover => 1,
+        }
+    }
+}
+
+impl FromStr for ProtectionKind {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "Scarecrow" => Ok(ProtectionKind::Scarecrow),
+            "StickyTrap" => Ok(ProtectionKind::StickyTrap),
+            "RowCover" => Ok(ProtectionKind::RowCover),
+            _ => Err(()),
+        }
+    }
+}
+
+impl std::fmt::Display for ProtectionKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            ProtectionKind::Scarecrow => write!(f, "Scarecrow"),
+            ProtectionKind::StickyTrap => write!(f, "StickyTrap"),
+            ProtectionKind::RowCover => write!(f, "RowCover"),
+        }
+    }
+}
+
+/// A protection sitting in the player's inventory
+pub struct Protection<'a> {
+    kind: ProtectionKind,
+    texture: Texture<'a>,
+}
+
+impl<'a> Protection<'a> {
+    pub fn new(
+        kind: ProtectionKind,
+        texture_creator: &'a TextureCreator<WindowContext>,
+    ) -> Protection<'a> {
+        Protection {
+            kind,
+            texture: texture_creator.load_texture(kind.tex_path()).unwrap(),
+        }
+    }
+}
+
+impl InventoryItemTrait for Protection<'_> {
+    fn get_value(&self) -> i32 {
+        1
+    }
+    fn texture(&self) -> &Texture {
+        &self.texture
+    }
+    fn src(&self) -> Rect {
+        Rect::new(0, 0, TILE_SIZE, TILE_SIZE)
+    }
+    /// Placing a protection needs the area's item list, so it is
+    /// handled in the game loop instead
+    fn inventory_input(
+        &self,
+        _square: (i32, i32),
+        _pop: &mut Population,
+    ) -> Option<(Option<CropType>, Option<genes::Genes>, Option<genes::Genes>)> {
+        None
+    }
+    fn to_save_string(&self) -> Option<String> {
+        Some(format!("protection;{}\n", self.kind))
+    }
+    fn placed_tex_path(&self) -> Option<&'static str> {
+        Some(self.kind.tex_path())
+    }
+}
+
+/// Where every protection on the farm is, for the nightly pest attack
+pub struct Protections {
+    placed: Vec<(ProtectionKind, i32, i32)>,
+}
+
+impl Protections {
+    /// Find every protection in `item_vec`
+    pub fn find_all(item_vec: &[Item]) -> Protections {
+        Protections {
+            placed: item_vec
+                .iter()
+                .filter_map(|i| {
+                    ProtectionKind::from_tex_path(i.tex_path())
+                        .map(|k| (k, i.x() / TILE_SIZE as i32, i.y() / TILE_SIZE as i32))
+                })
+                .collect(),
+        }
+    }
+
+    /// Returns true if a protection of the given kind reaches tile (x, y)
+    pub fn covers(&self, kind: ProtectionKind, x: u32, y: u32) -> bool {
+        self.placed.iter().any(|(k, px, py)| {
+            *k == kind
+                && (px - x as i32).abs() <= kind.radius()
+                && (py - y as i32).abs() <= kind.radius()
+        })
+    }
+
+    /// Share of a population's adults the farm's sticky traps kill tonight
+    pub fn trap_share(&self) -> f32 {
+        let traps = self
+            .placed
+            .iter()
+            .filter(|(k, _, _)| *k == ProtectionKind::StickyTrap)
+            .count();
+        (traps as f32 * TRAP_SHARE).min(MAX_TRAP_SHARE)
+    }
+}
@@ -0,0 +1,67 @@
+//! Module for the random numbers the pest simulation draws. They come
+//! from the thread's generator unless a seed has been set, so the
+//! headless simulator can replay a run exactly.
+
+use rand::rngs::StdRng;
+use rand::{RngCore, SeedableRng};
+use std::cell::RefCell;
+
+thread_local! {
+    /// Generator used instead of the thread's one once a seed is set
+    static SEEDED: RefCell<Option<StdRng>> = RefCell::new(None);
+}
+
+/// Draw every number on this thread from now on from a generator seeded with `seed`
+pub fn seed(seed: u64) {
+    SEEDED.with(|s| *s.borrow_mut() = Some(StdRng::seed_from_u64(seed)));
+}
+
+/// Handle to this thread's pest simulation generator. Use it anywhere
+/// `rand::thread_rng()` would be used.
+pub fn rng() -> PestRng {
+    PestRng
+}
+
+pub struct PestRng;
+
+impl PestRng {
+    fn with<T>(f: impl FnOnce(&mut dyn RngCore) -> T) -> T {
+        SEEDED.with(|s| match s.borrow_mut().as_mut() {
+            Some(r) => f(r),
+            None => f(&mut rand::thread_rng()),
+        })
+    }
+}
+
+impl RngCore for PestRng {
+    fn next_u32(&mut self) -> u32 {
+        PestRng::with(|r| r.next_u32())
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        PestRng::with(|r| r.next_u64())
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        PestRng::with(|r| r.fill_bytes(dest))
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        PestRng::with(|r| r.try_fill_bytes(dest))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::Rng;
+
+    #[test]
+    fn same_seed_same_numbers() {
+        seed(7);
+        let a: Vec<u32> = (0..10).map(|_| rng().gen()).collect();
+        seed(7);
+        let b: Vec<u32> = (0..10).map(|_| rng().gen()).collect();
+        assert_eq!(a, b);
+    }
+}
@@ -1,6 +1,16 @@
+use crate::calendar::{Calendar, Season};
+use crate::config::Config;
+use crate::economy::Economy;
+use crate::immigration::RegionalPool;
+use crate::market_item::MarketItem;
 use crate::pest_population::PestPopulation;
+use crate::pest_stats::{self, GenerationStats};
+use crate::predator::PredatorPopulation;
+use crate::store::Store;
+use crate::weather::WeatherState;
 use crate::{
-    crop, inventory, item, pest, pest_population, population, tile, BG_H, BG_W, TILE_SIZE,
+    colony, crop, disease, flower_bed, inventory, item, pest, pest_population, pest_species,
+    pesticide, player, population, protection, selection, sprinkler, terrain,
 };
 use sdl2::image::LoadTexture;
 use sdl2::rect::Rect;
@@ -9,46 +19,54 @@ use sdl2::video::WindowContext;
 use std::fs::File;
 use std::io::{Read, Write};
 
+/// Tile map file for the home area
+pub const HOME_MAP: &str = "saves/home_map.txt";
+/// Tile map file for the market area
+pub const MARKET_MAP: &str = "saves/market_map.txt";
+/// Size, in tiles, of an area whose save has no size line
+pub const DEFAULT_AREA_SIZE: (u32, u32) = (38, 38);
+
+/// Find the `size;<width>;<height>` line of an area save
+fn read_area_size(contents: &str) -> (u32, u32) {
+    for line in contents.lines() {
+        let results: Vec<&str> = line.split(";").collect();
+        if results[0] == "size" {
+            return (
+                results[1].parse::<u32>().unwrap(),
+                results[2].parse::<u32>().unwrap(),
+            );
+        }
+    }
+    DEFAULT_AREA_SIZE
+}
+
+/// Build an area's grid of tiles and empty crops, with terrain
+/// read from the tile map file at `map_path`
+fn new_population<'a>(
+    map_path: &str,
+    size: (u32, u32),
+    crop_texture: &'a Texture<'a>,
+    rotten_texture: &'a Texture<'a>,
+    tile_texture: &'a Texture<'a>,
+) -> population::Population<'a> {
+    let mut pop = population::Population::new(Vec::new());
+    pop.resize(
+        size.0,
+        size.1,
+        &terrain::TileMap::load(map_path),
+        tile_texture,
+        crop_texture,
+        rotten_texture,
+    );
+    pop
+}
+
 pub fn load_market<'a>(
     texture_creator: &'a TextureCreator<WindowContext>,
     crop_texture: &'a Texture<'a>,
     rotten_texture: &'a Texture<'a>,
     tile_texture: &'a Texture<'a>,
 ) -> (population::Population<'a>, Vec<item::Item<'a>>) {
-    let mut tile_vec = Vec::new();
-    for x in 0..((BG_W / TILE_SIZE) as i32) + 1 {
-        let mut sub_vec = Vec::new();
-        for y in 0..((BG_H / TILE_SIZE) as i32) + 1 {
-            sub_vec.push(population::CropTile::new(
-                tile::Tile::new(
-                    Rect::new(
-                        (TILE_SIZE as i32) * x,
-                        (TILE_SIZE as i32) * y,
-                        TILE_SIZE,
-                        TILE_SIZE,
-                    ),
-                    tile_texture,
-                ),
-                crop::Crop::new(
-                    Rect::new(
-                        (TILE_SIZE as i32) * x,
-                        (TILE_SIZE as i32) * y,
-                        TILE_SIZE,
-                        TILE_SIZE,
-                    ),
-                    0,
-                    crop_texture,
-                    rotten_texture,
-                    false,
-                    crop::CropType::None,
-                    None,
-                ),
-            ));
-        }
-        tile_vec.push(sub_vec);
-    }
-
-    let pop = population::Population::new(tile_vec);
     let mut market_item_vec = Vec::new();
     let mut market_file = File::open("saves/market_data.txt").expect("Can't open save market_file");
     let mut market_contents = String::new();
@@ -56,6 +74,13 @@ pub fn load_market<'a>(
     market_file
         .read_to_string(&mut market_contents)
         .expect("Can't read market_file");
+    let pop = new_population(
+        MARKET_MAP,
+        read_area_size(&market_contents),
+        crop_texture,
+        rotten_texture,
+        tile_texture,
+    );
     print!("{}", market_contents);
     for line in market_contents.lines() {
         let results: Vec<&str> = line.split(";").collect();
@@ -81,102 +106,183 @@ pub fn load_home<'a>(
     crop_texture: &'a Texture<'a>,
     rotten_texture: &'a Texture<'a>,
     tile_texture: &'a Texture<'a>,
+    pest_pops: &[PestPopulation],
 ) -> (population::Population<'a>, Vec<item::Item<'a>>) {
-    let mut tile_vec = Vec::new();
-    for x in 0..((BG_W / TILE_SIZE) as i32) + 1 {
-        let mut sub_vec = Vec::new();
-        for y in 0..((BG_H / TILE_SIZE) as i32) + 1 {
-            sub_vec.push(population::CropTile::new(
-                tile::Tile::new(
-                    Rect::new(
-                        (TILE_SIZE as i32) * x,
-                        (TILE_SIZE as i32) * y,
-                        TILE_SIZE,
-                        TILE_SIZE,
-                    ),
-                    tile_texture,
-                ),
-                crop::Crop::new(
-                    Rect::new(
-                        (TILE_SIZE as i32) * x,
-                        (TILE_SIZE as i32) * y,
-                        TILE_SIZE,
-                        TILE_SIZE,
-                    ),
-                    0,
-                    crop_texture,
-                    rotten_texture,
-                    false,
-                    crop::CropType::None,
-                    None,
-                ),
-            ));
-        }
-        tile_vec.push(sub_vec);
-    }
-    let mut pop = population::Population::new(tile_vec);
-
     let mut home_item_vec = Vec::new();
     //let mut crop_vec: Vec<crop::Crop> = Vec::new();
 
-    {
-        let mut home_file = File::open("saves/home_data.txt").expect("Can't open save home_file");
-        let mut home_contents = String::new();
-        home_file
-            .read_to_string(&mut home_contents)
-            .expect("Can't read home_file");
-        // print!("{}", home_contents);
-        for line in home_contents.lines() {
-            let results: Vec<&str> = line.split(";").collect();
-            if results[0] == "item" {
-                home_item_vec.push(item::Item::new(
-                    Rect::new(
-                        results[1].parse::<i32>().unwrap(),
-                        results[2].parse::<i32>().unwrap(),
-                        results[3].parse::<u32>().unwrap(),
-                        results[4].parse::<u32>().unwrap(),
-                    ),
-                    texture_creator.load_texture(results[5]).unwrap(),
-                    results[5].parse().unwrap(),
-                    results[6].parse::<bool>().unwrap(),
+    let mut home_file = File::open("saves/home_data.txt").expect("Can't open save home_file");
+    let mut home_contents = String::new();
+    home_file
+        .read_to_string(&mut home_contents)
+        .expect("Can't read home_file");
+    let mut pop = new_population(
+        HOME_MAP,
+        read_area_size(&home_contents),
+        crop_texture,
+        rotten_texture,
+        tile_texture,
+    );
+
+    // print!("{}", home_contents);
+    for line in home_contents.lines() {
+        let results: Vec<&str> = line.split(";").collect();
+        if results[0] == "item" {
+            home_item_vec.push(item::Item::new(
+                Rect::new(
+                    results[1].parse::<i32>().unwrap(),
+                    results[2].parse::<i32>().unwrap(),
+                    results[3].parse::<u32>().unwrap(),
+                    results[4].parse::<u32>().unwrap(),
+                ),
+                texture_creator.load_texture(results[5]).unwrap(),
+                results[5].parse().unwrap(),
+                results[6].parse::<bool>().unwrap(),
+            ));
+        } else if results[0] == "crop" {
+            let _x = results[1].parse::<i32>().unwrap();
+            let _y = results[2].parse::<i32>().unwrap();
+            pop.get_vec_mut()
+                .get_mut(_x as usize)
+                .unwrap()
+                .get_mut(_y as usize)
+                .unwrap()
+                .set_crop(crop::Crop::from_save_string(
+                    &results,
+                    crop_texture,
+                    rotten_texture,
                 ));
-            } else if results[0] == "crop" {
-                let _x = results[1].parse::<i32>().unwrap();
-                let _y = results[2].parse::<i32>().unwrap();
-                pop.get_vec_mut()
-                    .get_mut(_x as usize)
-                    .unwrap()
-                    .get_mut(_y as usize)
-                    .unwrap()
-                    .set_crop(crop::Crop::from_save_string(
-                        &results,
-                        crop_texture,
-                        rotten_texture,
-                    ));
-                // If crop is present, set tile as tilled
-                if results[5]
-                    .parse::<std::string::String>()
-                    .unwrap()
-                    .to_owned()
-                    != "None"
-                {
-                    let _tile = pop.get_tile_with_index_mut(_x as u32, _y as u32);
-                    _tile.set_tilled(true);
-                    if results[4].parse::<bool>().unwrap() {
-                        _tile.set_water(true);
-                    }
+            // If crop is present, set tile as tilled
+            if results[5]
+                .parse::<std::string::String>()
+                .unwrap()
+                .to_owned()
+                != "None"
+            {
+                let _tile = pop.get_tile_with_index_mut(_x as u32, _y as u32);
+                _tile.set_tilled(true);
+                if results[4].parse::<bool>().unwrap() {
+                    _tile.set_water(true);
                 }
             }
+        } else if results[0] == "pesticide" {
+            pop.set_pesticide(
+                results[1].parse::<u32>().unwrap(),
+                results[2].parse::<u32>().unwrap(),
+                results[3].parse::<u32>().unwrap(),
+            );
+        } else if results[0] == "disease" {
+            pop.set_disease(
+                results[1].parse::<u32>().unwrap(),
+                results[2].parse::<u32>().unwrap(),
+                Some(results[3].parse::<disease::Disease>().unwrap()),
+            );
+        } else if results[0] == "colony" {
+            if let Some((_x, _y, _colony)) = colony::Colony::from_save_string(&results, pest_pops) {
+                pop.set_colony(_x, _y, Some(_colony));
+            }
         }
     }
     return (pop, home_item_vec);
 }
 
-pub fn save_home(pop: population::Population, item_vec: Vec<item::Item>) {
+/// Every line of the home save, split on `;`
+fn home_lines() -> Vec<Vec<String>> {
+    let mut home_file = File::open("saves/home_data.txt").expect("Can't open save home_file");
+    let mut home_contents = String::new();
+    home_file
+        .read_to_string(&mut home_contents)
+        .expect("Can't read home_file");
+    home_contents
+        .lines()
+        .map(|line| line.split(";").map(String::from).collect::<Vec<String>>())
+        .collect()
+}
+
+/// Find the first line of the home save starting with `prefix`,
+/// split on `;`
+fn find_home_line(prefix: &str) -> Option<Vec<String>> {
+    home_lines()
+        .into_iter()
+        .find(|results| results[0] == prefix)
+}
+
+/// Read the date from the home save. A save without one starts
+/// on the first day of spring.
+pub fn load_calendar() -> Calendar {
+    match find_home_line("date") {
+        Some(results) => {
+            Calendar::from_save_string(&results.iter().map(|r| r.as_str()).collect::<Vec<&str>>())
+        }
+        None => Calendar::new(),
+    }
+}
+
+/// Read today's weather and the forecast from the home save. A save
+/// without them rolls new weather for `season`.
+pub fn load_weather(season: Season) -> WeatherState {
+    match find_home_line("weather") {
+        Some(results) => WeatherState::from_save_string(
+            &results.iter().map(|r| r.as_str()).collect::<Vec<&str>>(),
+        ),
+        None => WeatherState::new(season),
+    }
+}
+
+/// Read the player's money from the home save. A new game starts with
+/// the money for the difficulty in `config`.
+pub fn load_money(config: &Config) -> i32 {
+    find_home_line("money")
+        .and_then(|results| results.get(1)?.parse().ok())
+        .unwrap_or_else(|| player::starting_money(config))
+}
+
+/// Set up the valley's economy. Once its agents have settled, the
+/// prices and the market stall's stock from the home save are put back.
+pub fn load_economy() -> Economy {
+    let mut economy = Economy::new();
+    for results in home_lines() {
+        economy.load_save_string(&results.iter().map(|r| r.as_str()).collect::<Vec<&str>>());
+    }
+    economy
+}
+
+/// Put back how many of each of the store's items were in stock from the
+/// home save. A new game starts fully stocked.
+pub fn load_stock(items: &mut [MarketItem]) {
+    if let Some(results) = find_home_line("stock") {
+        for (item, amount) in items.iter_mut().zip(&results[1..]) {
+            item.set_amount(amount.parse().unwrap());
+        }
+    }
+}
+
+pub fn save_home(
+    pop: population::Population,
+    item_vec: Vec<item::Item>,
+    calendar: &Calendar,
+    weather: &WeatherState,
+    pest_pops: &[PestPopulation],
+    money: i32,
+    economy: &Economy,
+    store: &Store,
+) {
     let mut file_to_save = match File::create("saves/home_data.txt") {
         Err(why) => panic!("couldn't create home_data.txt: {}", why),
         Ok(file_to_save) => file_to_save,
     };
+    let output = format!("size;{};{}\n", pop.width(), pop.height())
+        + &calendar.to_save_string()
+        + &weather.to_save_string()
+        + &format!("money;{}\n", money)
+        + &economy.to_save_string()
+        + &store.to_save_string();
+    match file_to_save.write_all(output.as_ref()) {
+        Err(why) => panic!("couldn't write to home_data.txt: {}", why),
+        Ok(_) => {
+            println!("successfully wrote size, date, money, prices and stock to home_data.txt")
+        }
+    }
     for item in item_vec {
         let output = "item;".to_owned()
             + &item.x().to_string()
@@ -197,9 +303,32 @@ pub fn save_home(pop: population::Population, item_vec: Vec<item::Item>) {
         }
     }
 
-    for _x in 0..((BG_W / TILE_SIZE) as i32 + 1) {
-        for _y in 0..((BG_H / TILE_SIZE) as i32 + 1) {
-            let _c = pop.get_crop_with_index(_x as u32, _y as u32);
+    for _x in 0..pop.width() {
+        for _y in 0..pop.height() {
+            let nights = pop.get_pesticide(_x, _y);
+            if nights > 0 {
+                let output = format!("pesticide;{};{};{}\n", _x, _y, nights);
+                match file_to_save.write_all(output.as_ref()) {
+                    Err(why) => panic!("couldn't write to home_data.txt: {}", why),
+                    Ok(_) => println!("successfully wrote pesticide to home_data.txt"),
+                }
+            }
+            // Colonies and diseases are saved whatever the tile holds, so
+            // eggs overwintering in bare soil aren't lost
+            let mut output = String::new();
+            if let Some(_colony) = pop.get_colony(_x, _y) {
+                output.push_str(&_colony.to_save_string(_x, _y, pest_pops));
+            }
+            if let Some(_disease) = pop.get_disease(_x, _y) {
+                output.push_str(&format!("disease;{};{};{}\n", _x, _y, _disease));
+            }
+            if !output.is_empty() {
+                match file_to_save.write_all(output.as_ref()) {
+                    Err(why) => panic!("couldn't write to home_data.txt: {}", why),
+                    Ok(_) => println!("successfully wrote colony to home_data.txt"),
+                }
+            }
+            let _c = pop.get_crop_with_index(_x, _y);
             match _c.get_crop_type() {
                 "None" => {}
                 _ => {
@@ -227,8 +356,8 @@ pub fn save_inventory(inventory: &inventory::Inventory) {
         Err(why) => panic!("Couldn't create inventory_data.txt: {}", why),
         Ok(file_to_save) => file_to_save,
     };
-    // Save all crops in Inventory slots 3 through 10
-    for i in 3..11 {
+    // Save all crops, sprinklers, pesticide and flower beds past the tool slots
+    for i in 3..inventory::INVENTORY_SLOTS {
         if let Some(v) = inventory.get_inventory_slot(i) {
             for j in 0..v.get_len() {
                 // Save each crop
@@ -247,6 +376,7 @@ pub fn save_inventory(inventory: &inventory::Inventory) {
 
 pub fn load_inventory<'a>(
     inventory: &mut inventory::Inventory<'a>,
+    texture_creator: &'a TextureCreator<WindowContext>,
     crop_texture: &'a Texture<'a>,
     rotten_texture: &'a Texture<'a>,
 ) {
@@ -264,35 +394,148 @@ pub fn load_inventory<'a>(
                 crop_texture,
                 rotten_texture,
             ));
+        } else if results[0] == "pesticide" {
+            inventory.add_pesticide(pesticide::Pesticide::new(texture_creator));
+        } else if results[0] == "fungicide" {
+            inventory.add_fungicide(disease::Fungicide::new(texture_creator));
+        } else if results[0] == "protection" {
+            let kind = results[1].parse::<protection::ProtectionKind>().unwrap();
+            inventory.add_protection(kind, protection::Protection::new(kind, texture_creator));
+        } else if results[0] == "flower_bed" {
+            inventory.add_flower_bed(flower_bed::FlowerBed::new(texture_creator));
+        } else if results[0] == "sprinkler" {
+            inventory.add_sprinkler(sprinkler::Sprinkler::new(
+                results[1].parse::<sprinkler::SprinklerTier>().unwrap(),
+                texture_creator,
+            ));
         }
     }
 }
 
-pub fn load_pests<'a>() -> PestPopulation {
-    let mut pest_pop = pest_population::PestPopulation::new();
+/// Load a population for every species in `pest_species::SPECIES_FILE`.
+/// Each species' pests are saved in their own section of `pest_data.txt`,
+/// starting with a `species;<name>` line. Pests saved before any section
+/// belong to the first species. Species with no section yet start with a
+/// full population; a section with no pests means the species died out.
+pub fn load_pests(config: &Config) -> Vec<PestPopulation> {
+    let mut pest_pops: Vec<PestPopulation> = pest_species::load_all(pest_species::SPECIES_FILE)
+        .into_iter()
+        .map(|s| {
+            pest_population::PestPopulation::new(s, selection::from_config(config.get("selection")))
+        })
+        .collect();
     let mut pest_file = File::open("saves/pest_data.txt").expect("Can't open save home_file");
     let mut pest_contents = String::new();
     pest_file
         .read_to_string(&mut pest_contents)
         .expect("Can't read home_file");
+    let mut cur = Some(0);
+    let mut seen = vec![false; pest_pops.len()];
     for line in pest_contents.lines() {
         let results: Vec<&str> = line.split(";").collect();
-        pest_pop.add_pest(pest::Pest::from_save_string(results));
+        if results[0] == "species" {
+            // Pests of species that have been removed are dropped
+            cur = pest_pops
+                .iter()
+                .position(|p| p.get_species().get_name() == results[1]);
+            if let Some(i) = cur {
+                seen[i] = true;
+            }
+        } else if results[0] == "predator" {
+            // Read by load_predators
+        } else if results[0] == "pool" {
+            if let (Some(i), Some(pool)) = (cur, RegionalPool::from_save_string(&results)) {
+                pest_pops[i].set_pool(pool);
+            }
+        } else if results[0] == "history" {
+            if let Some(i) = cur {
+                pest_pops[i].set_resistance_history(
+                    results[1..]
+                        .iter()
+                        .map(|r| r.parse::<f32>().unwrap())
+                        .collect(),
+                );
+            }
+        } else if let Some(i) = cur {
+            pest_pops[i].add_pest(pest::Pest::from_save_string(results));
+        }
+    }
+    load_pest_stats(&mut pest_pops);
+    for (i, pest_pop) in pest_pops.iter_mut().enumerate() {
+        if !seen[i] && pest_pop.get_length() == 0 {
+            pest_pop.fill_pest_population();
+        }
+        pest_pop.find_avg_attack_chance();
     }
-    if pest_pop.get_length() < pest_population::POP_SIZE {
-        pest_pop.fill_pest_population();
+    pest_pops
+}
+
+/// Load each population's generation statistics from the stats file.
+/// Saves from before there were statistics start with none.
+fn load_pest_stats(pest_pops: &mut [PestPopulation]) {
+    let mut stats_contents = String::new();
+    if let Ok(mut stats_file) = File::open(pest_stats::STATS_FILE) {
+        stats_file
+            .read_to_string(&mut stats_contents)
+            .expect("Can't read pest_stats.csv");
+    }
+    let mut histories = vec![Vec::new(); pest_pops.len()];
+    for line in stats_contents.lines() {
+        if let Some((species, stats)) = GenerationStats::from_csv(line) {
+            // Statistics of species that have been removed are dropped
+            if let Some(i) = pest_pops
+                .iter()
+                .position(|p| p.get_species().get_name() == species)
+            {
+                histories[i].push(stats);
+            }
+        }
+    }
+    for (pest_pop, history) in pest_pops.iter_mut().zip(histories) {
+        pest_pop.set_stats(history);
+    }
+}
+
+/// Load the ladybugs from their line in `pest_data.txt`. Saves from
+/// before there were ladybugs start with a new population.
+pub fn load_predators() -> PredatorPopulation {
+    let mut pest_contents = String::new();
+    if let Ok(mut pest_file) = File::open("saves/pest_data.txt") {
+        pest_file
+            .read_to_string(&mut pest_contents)
+            .expect("Can't read pest_data.txt");
     }
-    pest_pop.find_avg_attack_chance();
-    pest_pop
+    pest_contents
+        .lines()
+        .find_map(|line| PredatorPopulation::from_save_string(&line.split(";").collect::<Vec<_>>()))
+        .unwrap_or_else(PredatorPopulation::new)
 }
 
-pub fn save_pests<'a>(p: PestPopulation) {
+pub fn save_pests(pest_pops: &[PestPopulation], predators: &PredatorPopulation) {
     let mut file_to_save = match File::create("saves/pest_data.txt") {
         Err(why) => panic!("Couldn't create inventory_data.txt: {}", why),
         Ok(file_to_save) => file_to_save,
     };
-    for pest in 0..p.get_length() {
-        let output = p.get_pest(pest).to_save_string();
+    match file_to_save.write_all(predators.to_save_string().as_ref()) {
+        Err(why) => {
+            panic!("couldn't write to pest_data.txt: {}", why)
+        }
+        Ok(_) => {}
+    }
+    for p in pest_pops {
+        let mut output = format!("species;{}\n", p.get_species().get_name());
+        output.push_str(&p.get_pool().to_save_string());
+        // Average pesticide resistance of each generation, oldest first
+        if !p.get_resistance_history().is_empty() {
+            output.push_str("history");
+            for r in p.get_resistance_history() {
+                output.push_str(&format!(";{}", r));
+            }
+            output.push('\n');
+        }
+        for pest in 0..p.get_length() {
+            output.push_str(&p.get_pest(pest).to_save_string());
+        }
         match file_to_save.write_all(output.as_ref()) {
             Err(why) => {
                 panic!("couldn't write to pest_data.txt: {}", why)
@@ -300,4 +543,25 @@ pub fn save_pests<'a>(p: PestPopulation) {
             Ok(_) => {}
         }
     }
+    save_pest_stats(pest_pops);
+}
+
+/// Save every population's generation statistics to the stats file
+fn save_pest_stats(pest_pops: &[PestPopulation]) {
+    let mut file_to_save = match File::create(pest_stats::STATS_FILE) {
+        Err(why) => panic!("Couldn't create pest_stats.csv: {}", why),
+        Ok(file_to_save) => file_to_save,
+    };
+    let mut output = GenerationStats::csv_header();
+    for p in pest_pops {
+        for (generation, stats) in p.get_stats().iter().enumerate() {
+            output.push_str(&stats.to_csv(p.get_species().get_name(), generation));
+        }
+    }
+    match file_to_save.write_all(output.as_ref()) {
+        Err(why) => {
+            panic!("couldn't write to pest_stats.csv: {}", why)
+        }
+        Ok(_) => {}
+    }
 }
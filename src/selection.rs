@@ -0,0 +1,160 @@
+//! Module for the ways parents are picked when a pest population breeds
+//! its next generation. The strategy is chosen in `saves/config.txt`.
+
+use rand::Rng;
+
+use crate::rng;
+
+/// Picks the parents of the next generation from each pest's weight
+pub trait SelectionStrategy {
+    /// Indices of `count` parents, chosen using `weights`. Higher weights
+    /// should be picked more often. Weights are never negative and at
+    /// least one is above 0.
+    fn select(&self, weights: &[f32], count: usize) -> Vec<usize>;
+
+    /// Number of the best pests copied unchanged into the next generation
+    fn elites(&self) -> usize {
+        0
+    }
+}
+
+/// Each pest is picked with a chance proportional to its weight
+pub struct Roulette;
+
+impl SelectionStrategy for Roulette {
+    fn select(&self, weights: &[f32], count: usize) -> Vec<usize> {
+        spin(weights, count)
+    }
+}
+
+/// Pick `count` indices with chances proportional to `weights`
+fn spin(weights: &[f32], count: usize) -> Vec<usize> {
+    let mut cumulative = Vec::with_capacity(weights.len());
+    let mut total = 0.0;
+    for w in weights {
+        total += w;
+        cumulative.push(total);
+    }
+    let mut rng = rng::rng();
+    (0..count)
+        .map(|_| {
+            let r = rng.gen_range(0.0..total);
+            // First index whose running total passes r; zero weights
+            // share their total with the index before and are skipped
+            cumulative
+                .partition_point(|c| *c <= r)
+                .min(weights.len() - 1)
+        })
+        .collect()
+}
+
+/// The best of `size` randomly chosen pests wins each pick
+pub struct Tournament {
+    pub size: usize,
+}
+
+impl SelectionStrategy for Tournament {
+    fn select(&self, weights: &[f32], count: usize) -> Vec<usize> {
+        let mut rng = rng::rng();
+        (0..count)
+            .map(|_| {
+                (0..self.size.max(1))
+                    .map(|_| rng.gen_range(0..weights.len()))
+                    .max_by(|a, b| weights[*a].partial_cmp(&weights[*b]).unwrap())
+                    .unwrap()
+            })
+            .collect()
+    }
+}
+
+/// Like roulette, but chances go by place in the ranking rather than
+/// by weight, so a few very fit pests can't take over the population
+pub struct Rank;
+
+impl SelectionStrategy for Rank {
+    fn select(&self, weights: &[f32], count: usize) -> Vec<usize> {
+        let mut order: Vec<usize> = (0..weights.len()).collect();
+        order.sort_by(|a, b| weights[*a].partial_cmp(&weights[*b]).unwrap());
+        // The worst pest gets rank 1, the best gets rank n
+        let ranks: Vec<f32> = (1..=weights.len()).map(|r| r as f32).collect();
+        spin(&ranks, count).into_iter().map(|i| order[i]).collect()
+    }
+}
+
+/// Copies the best `count` pests unchanged, then picks parents for the
+/// rest with another strategy
+pub struct Elitism {
+    pub count: usize,
+    pub inner: Box<dyn SelectionStrategy>,
+}
+
+impl SelectionStrategy for Elitism {
+    fn select(&self, weights: &[f32], count: usize) -> Vec<usize> {
+        self.inner.select(weights, count)
+    }
+
+    fn elites(&self) -> usize {
+        self.count
+    }
+}
+
+/// Build a strategy from a config value such as `Roulette`, `Rank`,
+/// `Tournament;3` or `Elitism;10;Tournament;3`. Falls back to roulette
+/// if the value can't be read.
+pub fn from_config(v: &[String]) -> Box<dyn SelectionStrategy> {
+    match v.first().map(|s| s.as_str()) {
+        Some("Tournament") => Box::new(Tournament {
+            size: v.get(1).and_then(|s| s.parse().ok()).unwrap_or(3),
+        }),
+        Some("Rank") => Box::new(Rank),
+        Some("Elitism") => Box::new(Elitism {
+            count: v.get(1).and_then(|s| s.parse().ok()).unwrap_or(0),
+            inner: from_config(v.get(2..).unwrap_or(&[])),
+        }),
+        Some("Roulette") | None => Box::new(Roulette),
+        Some(other) => {
+            println!("Unknown selection strategy {}, using Roulette", other);
+            Box::new(Roulette)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(s: &str) -> Vec<String> {
+        s.split(";").map(String::from).collect()
+    }
+
+    #[test]
+    fn roulette_skips_zero_weights() {
+        let weights = vec![0.0, 1.0, 0.0, 2.0, 0.0];
+        for i in Roulette.select(&weights, 1000) {
+            assert!(i == 1 || i == 3);
+        }
+    }
+
+    #[test]
+    fn full_tournament_picks_best() {
+        let weights = vec![0.1, 0.9, 0.4];
+        let t = Tournament { size: 50 };
+        assert!(t.select(&weights, 100).iter().all(|i| *i == 1));
+    }
+
+    #[test]
+    fn rank_picks_every_pest() {
+        let weights = vec![0.0, 0.5, 100.0];
+        let picks = Rank.select(&weights, 2000);
+        for i in 0..weights.len() {
+            assert!(picks.contains(&i));
+        }
+    }
+
+    #[test]
+    fn elitism_from_config() {
+        let s = from_config(&config("Elitism;10;Tournament;3"));
+        assert_eq!(s.elites(), 10);
+        assert_eq!(s.select(&[1.0, 2.0], 5).len(), 5);
+    }
+}
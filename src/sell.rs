@@ -0,0 +1,127 @@
+//! Module for the sell screen at the market stall, where the player
+//! sells harvested crops and spare seeds. The stall pays the market
+//! price for each, more for a high Value gene and less for a low one,
+//! and sells them on to the rest of the valley.
+
+use sdl2::pixels::Color;
+use sdl2::rect::Rect;
+use sdl2::render::{Texture, WindowCanvas};
+
+use crate::commodities::CommodityKind;
+use crate::crop::CropType;
+use crate::economy::Economy;
+use crate::hud;
+use crate::inventory::Inventory;
+use crate::player::Player;
+
+/// Text scale of the sell screen
+const SCALE: u32 = 4;
+/// Top left corner of the sell screen
+const X: i32 = 150;
+const Y: i32 = 30;
+/// Share of the market price paid for something with a Value gene of
+/// 0. Each point of Value adds the whole market price.
+const BASE_PRICE_SHARE: f32 = 0.5;
+
+/// What the stall pays for one crop, or seed if `seedy`, of type `t`
+/// with a Value gene of `value`
+pub fn offer(economy: &Economy, t: CropType, seedy: bool, value: f32) -> i32 {
+    match CommodityKind::of_crop(t, seedy) {
+        Some(c) => {
+            let price = economy.get_price(c) * (BASE_PRICE_SHARE + value.max(0.0));
+            (price.round() as i32).max(1)
+        }
+        None => 0,
+    }
+}
+
+/// Which of the player's crop and seed stacks the sell screen has
+/// selected, and what the last sale made
+pub struct SellScreen {
+    selected: usize,
+    last_sale: Option<i32>,
+}
+
+impl SellScreen {
+    pub fn new() -> SellScreen {
+        SellScreen {
+            selected: 0,
+            last_sale: None,
+        }
+    }
+
+    /// Move the selection up or down, staying within `num_stacks`
+    pub fn navigate(&mut self, dir: i32, num_stacks: usize) {
+        self.selected =
+            (self.selected as i32 + dir).clamp(0, num_stacks.max(1) as i32 - 1) as usize;
+    }
+
+    /// Sell the best crop or seed of the selected stack. Returns what the
+    /// player was paid, or None if there was nothing to sell.
+    pub fn sell(&mut self, player: &mut Player, economy: &mut Economy) -> Option<i32> {
+        let stacks = player.get_inventory().crop_stacks();
+        let (t, seedy, _) = *stacks.get(self.selected)?;
+        let value = player.get_inventory().best_crop_value(t, seedy)?;
+        let price = offer(economy, t, seedy, value);
+        if !player.get_inventory().take_crop(t, seedy) {
+            return None;
+        }
+        player.earn(price);
+        economy.supply(CommodityKind::of_crop(t, seedy)?, 1.0);
+        self.navigate(0, player.get_inventory().crop_stacks().len());
+        self.last_sale = Some(price);
+        Some(price)
+    }
+
+    /// Draw every stack the player could sell with what the stall pays
+    /// for its best item
+    pub fn draw(
+        &self,
+        wincan: &mut WindowCanvas,
+        font: &Texture,
+        inventory: &Inventory,
+        money: i32,
+        economy: &Economy,
+    ) {
+        let stacks = inventory.crop_stacks();
+        let mut lines = vec![format!("Sell to the stall   ${}", money), String::new()];
+        for (t, seedy, count) in &stacks {
+            let value = inventory.best_crop_value(*t, *seedy).unwrap_or(0.0);
+            lines.push(format!(
+                "{:?} {} x{}   ${}",
+                t,
+                if *seedy { "seeds" } else { "crops" },
+                count,
+                offer(economy, *t, *seedy, value)
+            ));
+        }
+        if stacks.is_empty() {
+            lines.push("Nothing to sell".to_string());
+        }
+        lines.push(String::new());
+        if let Some(price) = self.last_sale {
+            lines.push(format!("Sold for ${}", price));
+        }
+        lines.push("Up/Down: choose  P: sell  Q: back".to_string());
+        hud::draw_panel(wincan, font, &lines, X, Y, SCALE);
+
+        if !stacks.is_empty() {
+            let padding = 2 * SCALE;
+            let row = hud::text_height(SCALE) + SCALE;
+            let width = lines
+                .iter()
+                .map(|l| hud::text_width(l, SCALE))
+                .max()
+                .unwrap_or(0);
+            wincan.set_draw_color(Color::RGBA(255, 0, 0, 60));
+            wincan
+                .fill_rect(Rect::new(
+                    X,
+                    Y + (padding + (2 + self.selected as u32) * row) as i32,
+                    width + 2 * padding,
+                    row,
+                ))
+                .unwrap();
+        }
+    }
+}
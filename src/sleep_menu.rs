@@ -1,11 +1,11 @@
+use crate::calendar::Season;
+use crate::crop::CropType;
 use crate::player::Player;
 // Module for sleeping menu and code.
 use crate::population::Population;
+use crate::weather::{Weather, WeatherState};
 use crate::Menu;
-use crate::BG_H;
-use crate::BG_W;
-use crate::TILE_SIZE;
-use crate::{genes, pest, pest_population};
+use crate::{colony, disease, flower_bed, item, pesticide, sprinkler, terrain, weather};
 
 use sdl2::image::LoadTexture;
 use sdl2::keyboard::Keycode;
@@ -19,191 +19,345 @@ use std::thread;
 use std::time::Duration;
 
 //Imported to see if it's a bug night or not
+use crate::clock::WorldClock;
+use crate::economy::Economy;
 use crate::pest_population::PestPopulation;
+use crate::predator::PredatorPopulation;
+use crate::protection::{self, ProtectionKind, Protections};
+use crate::store::Store;
 use rand::Rng;
 
+/// Share of the player's money taken when they pass out
+const PASS_OUT_FEE_RATE: f32 = 0.1;
+/// Passing out never costs more than this
+const MAX_PASS_OUT_FEE: i32 = 1000;
+
 pub fn start_sleep_menu<'a>(
     mut in_menu: Option<Menu>,
     wincan: &mut WindowCanvas,
     keystate: HashSet<Keycode>,
     player: &mut Player,
     pop: &mut Population<'a>,
+    item_vec: &[item::Item],
     r: Rect,
-    pest_pop: &mut PestPopulation,
+    pest_pops: &mut [PestPopulation],
+    predators: &mut PredatorPopulation,
+    clock: &mut WorldClock,
+    weather: &mut WeatherState,
+    economy: &mut Economy,
+    store: &mut Store,
 ) -> Option<Menu> {
-    let texture_creator = wincan.texture_creator();
     if keystate.contains(&Keycode::Y) {
         //Player has selected yes
+        sleep(
+            wincan, player, pop, item_vec, r, pest_pops, predators, clock, weather, economy, store,
+        );
+        in_menu = None;
+        return in_menu;
+    } else if keystate.contains(&Keycode::N) {
+        //Player has chosen not to sleep
+        in_menu = None;
+        return in_menu;
+    } else {
+        return in_menu;
+    }
+}
+
+/// The player stayed up until 2 AM and passes out wherever they are.
+/// Someone carries them home for a fee, then the night passes as usual.
+pub fn pass_out<'a>(
+    wincan: &mut WindowCanvas,
+    player: &mut Player,
+    pop: &mut Population<'a>,
+    item_vec: &[item::Item],
+    r: Rect,
+    pest_pops: &mut [PestPopulation],
+    predators: &mut PredatorPopulation,
+    clock: &mut WorldClock,
+    weather: &mut WeatherState,
+    economy: &mut Economy,
+    store: &mut Store,
+) {
+    let fee = ((player.get_money() as f32 * PASS_OUT_FEE_RATE) as i32).min(MAX_PASS_OUT_FEE);
+    let paid = player.charge(fee);
+    println!("You passed out! It cost {} to get you home", paid);
+    sleep(
+        wincan, player, pop, item_vec, r, pest_pops, predators, clock, weather, economy, store,
+    );
+}
 
-        //Generate a random number between 0.0 and 1.0. If that number is lower than the pest populations
-        //average chance to attack bugs will attack that night.
-        let mut rng = rand::thread_rng();
-        // let bug_night_result = rng.gen_range(0.0..1.0);
+/// Fade out, run the nightly step on the farm, then fade back in
+/// to the next morning
+pub fn sleep<'a>(
+    wincan: &mut WindowCanvas,
+    player: &mut Player,
+    pop: &mut Population<'a>,
+    item_vec: &[item::Item],
+    r: Rect,
+    pest_pops: &mut [PestPopulation],
+    predators: &mut PredatorPopulation,
+    clock: &mut WorldClock,
+    weather: &mut WeatherState,
+    economy: &mut Economy,
+    store: &mut Store,
+) {
+    let texture_creator = wincan.texture_creator();
+    let season = clock.get_calendar().get_season();
+
+    //Generate a random number between 0.0 and 1.0 for each species in season. If that number is lower
+    //than the species' average chance to attack, that species' bugs will attack that night.
+    let mut rng = rand::thread_rng();
+    let mut arriving = Vec::new();
+    for (i, pest_pop) in pest_pops.iter().enumerate() {
         let bug_night_result: f32 = rng.gen();
-        println!("{}", bug_night_result);
-        println!("{}", pest_pop.get_avg_attack_chance());
-        //let bug_night_result = 5;
-
-        //Cut to black and then fade into night scene
-        if bug_night_result > pest_pop.get_avg_attack_chance() {
-            let mut i = 0;
-            while i < 254 {
-                wincan
-                    .copy(
-                        &texture_creator
-                            .load_texture("src/images/sleeping_screen.png")
-                            .unwrap(),
-                        None,
-                        None,
-                    )
-                    .unwrap();
-                wincan.set_draw_color(Color::RGBA(0, 0, 0, 255 - i));
-                wincan.fill_rect(r).unwrap();
-                wincan.present();
-                thread::sleep(Duration::from_millis(1));
-                i = i + 2;
-            }
-        } else {
-            let mut i = 0;
-            while i < 254 {
-                wincan
-                    .copy(
-                        &texture_creator
-                            .load_texture("src/images/sleeping_screen_spiders.png")
-                            .unwrap(),
-                        None,
-                        None,
-                    )
-                    .unwrap();
-                wincan.set_draw_color(Color::RGBA(0, 0, 0, 255 - i));
-                wincan.fill_rect(r).unwrap();
-                wincan.present();
-                // thread::sleep(Duration::from_millis(1));
-                thread::sleep(Duration::from_micros(250));
-                i = i + 2;
-            }
+        if pest_pop.get_species().spawns_in(season)
+            && bug_night_result <= pest_pop.get_avg_attack_chance()
+        {
+            println!("{}s are coming tonight", pest_pop.get_species().get_name());
+            arriving.push(i);
         }
+    }
+    let bug_night = !arriving.is_empty();
 
-        //The fading code is ripped out of the method because I wanted
-        // the growing to happen while the player could not see the screen.
-        // Grow crops
-        for _x in 0..((BG_W / TILE_SIZE) as i32 + 1) {
-            for _y in 0..((BG_H / TILE_SIZE) as i32 + 1) {
-                let n = pop.get_neighbors(_x, _y);
-
-                if bug_night_result <= pest_pop.get_avg_attack_chance() {
-                    // Picks a random pest; if it is has a higher attack than a
-                    // crops pest resistence, remove the crop from the game (RIP)
-                    // Otherwise pest is removed from the game.
-                    if let Some(g) = pop
-                        .get_crop_with_index_mut(_x as u32, _y as u32)
-                        .get_gene(genes::GeneType::PestResistance)
-                    {
-                        let mut rng = rand::thread_rng();
-                        let pest_index = rng.gen_range(0..pest_population::POP_SIZE);
-                        let attacking_pest = pest_pop.get_pest(pest_index);
-                        let mut _c = pop.get_crop_with_index_mut(_x as u32, _y as u32);
-                        if attacking_pest.attack_crop(_c) > g {
-                            // Change a crop to show the rotten sprite
-                            _c.set_rotten(true);
-                            _c.set_stage(0);
-                            _c.set_water(false);
-                            _c.set_genes(None);
-                        } else {
-                            pest_pop.kill_pest(pest_index);
-                            pest_pop.add_pest(pest::Pest::new());
-                        }
-                    }
-                }
+    //Cut to black and then fade into night scene
+    if !bug_night {
+        let mut i = 0;
+        while i < 254 {
+            wincan
+                .copy(
+                    &texture_creator
+                        .load_texture("src/images/sleeping_screen.png")
+                        .unwrap(),
+                    None,
+                    None,
+                )
+                .unwrap();
+            wincan.set_draw_color(Color::RGBA(0, 0, 0, 255 - i));
+            wincan.fill_rect(r).unwrap();
+            wincan.present();
+            thread::sleep(Duration::from_millis(1));
+            i = i + 2;
+        }
+    } else {
+        let mut i = 0;
+        while i < 254 {
+            wincan
+                .copy(
+                    &texture_creator
+                        .load_texture("src/images/sleeping_screen_spiders.png")
+                        .unwrap(),
+                    None,
+                    None,
+                )
+                .unwrap();
+            wincan.set_draw_color(Color::RGBA(0, 0, 0, 255 - i));
+            wincan.fill_rect(r).unwrap();
+            wincan.present();
+            // thread::sleep(Duration::from_millis(1));
+            thread::sleep(Duration::from_micros(250));
+            i = i + 2;
+        }
+    }
 
-                let mut _c = pop.get_crop_with_index_mut(_x as u32, _y as u32);
-                match _c.get_crop_type() {
-                    "None" => {
-                        _c.set_water(false);
-                        let mut _t = pop.get_tile_with_index_mut(_x as u32, _y as u32);
-                        _t.set_tilled(false);
-                        _t.set_water(false);
-                    }
-                    _ => {
-                        if !_c.rotten() {
-                            _c.grow();
-                            _c.pollinate(n);
-                            if !_c.get_watered() {
-                                pop.get_tile_with_index_mut(_x as u32, _y as u32)
-                                    .set_water(false);
-                            } else {
-                                pop.get_tile_with_index_mut(_x as u32, _y as u32)
-                                    .set_water(true);
+    //The fading code is ripped out of the method because I wanted
+    // the growing to happen while the player could not see the screen.
+    // Sprinklers and water sources water first so their crops can grow tonight
+    sprinkler::water_from_sprinklers(item_vec, pop);
+    terrain::irrigate(pop);
+
+    // Tomorrow's forecast comes true overnight
+    let tonight = weather.get_tomorrow();
+    match tonight {
+        Weather::Rain => weather::rain(pop),
+        Weather::Frost => weather::frost(pop),
+        _ => {}
+    }
+
+    // Pests land on the farm on their bug nights, then every colony
+    // attacks its crop and spreads
+    // Diseases spread between crops, faster in the rain
+    disease::night(pop, tonight == Weather::Rain);
+
+    let protections = Protections::find_all(item_vec);
+    for i in arriving {
+        colony::arrive(pop, pest_pops, i, &protections);
+    }
+    colony::night(pop, pest_pops, season, &protections);
+
+    // Ladybugs pick off colonies, most of all near flower beds, and any
+    // pesticide still on the farm harms them as well as the pests
+    let flower_beds = flower_bed::find_all(item_vec);
+    let sprayed = pesticide::sprayed_tiles(pop);
+    if season != Season::Winter {
+        predators.hunt_colonies(pop, &flower_beds);
+    }
+    pesticide::wear_off(pop);
+
+    // Grow crops
+    for _x in 0..pop.width() as i32 {
+        for _y in 0..pop.height() as i32 {
+            let n = pop.get_neighbors(_x, _y);
+
+            let mut _c = pop.get_crop_with_index_mut(_x as u32, _y as u32);
+            match _c.get_crop_type() {
+                "None" => {
+                    _c.set_water(false);
+                    let mut _t = pop.get_tile_with_index_mut(_x as u32, _y as u32);
+                    _t.set_tilled(false);
+                    _t.set_water(false);
+                }
+                _ => {
+                    if !_c.rotten() {
+                        _c.heal();
+                        // Crops stall outside of their seasons
+                        if _c.get_crop_type_enum().grows_in(season) {
+                            _c.grow(tonight.evaporation());
+                            // Row covers keep out pollen as well as pests
+                            if !protections.covers(ProtectionKind::RowCover, _x as u32, _y as u32)
+                                || rng.gen::<f32>() < protection::COVERED_POLLINATION
+                            {
+                                _c.pollinate(n);
                             }
                         }
+                        if !_c.get_watered() {
+                            pop.get_tile_with_index_mut(_x as u32, _y as u32)
+                                .set_water(false);
+                        } else {
+                            pop.get_tile_with_index_mut(_x as u32, _y as u32)
+                                .set_water(true);
+                        }
                     }
                 }
-                // drop(_c);
-                // _c.pollinate(pop);
-                //_c.set_water(false);
-                // Set tile watered to false
-                // if pop
-                //     .get_crop_with_index_mut(_x as u32, _y as u32)
-                //     .get_watered()
-                //     == false
             }
+            // drop(_c);
+            // _c.pollinate(pop);
+            //_c.set_water(false);
+            // Set tile watered to false
+            // if pop
+            //     .get_crop_with_index_mut(_x as u32, _y as u32)
+            //     .get_watered()
+            //     == false
         }
+    }
 
-        pest_pop.next_generation();
+    // Pests in season breed from how their colonies fed tonight, which
+    // decides who breeds and how many. Out of season they lie dormant.
+    // Ladybugs thin out the pests in season first
+    if season == Season::Winter {
+        predators.overwinter();
+    } else {
+        let active: Vec<bool> = pest_pops
+            .iter()
+            .map(|p| p.get_species().spawns_in(season))
+            .collect();
+        predators.hunt(pest_pops, &active, flower_beds.len(), sprayed);
+    }
 
-        // Eat dinner.
-        let hunger = player.dinner();
-        if hunger > 0 {
-            println!("how sad, your are still {} hungery", hunger)
+    let hosts = colony::host_crops(pop);
+    for pest_pop in pest_pops.iter_mut() {
+        pest_pop.drift_pool();
+        if !pest_pop.get_species().spawns_in(season) {
+            pest_pop.go_dormant(season == Season::Winter);
+            continue;
+        }
+        let extinct = pest_pop.is_extinct();
+        let arrivals = pest_pop.immigrate();
+        if arrivals > 0 && extinct {
+            println!(
+                "{}s have moved back onto the farm",
+                pest_pop.get_species().get_name()
+            );
+        } else if arrivals > 0 {
+            println!(
+                "{} {}s have wandered onto the farm",
+                arrivals,
+                pest_pop.get_species().get_name()
+            );
+        }
+        if pest_pop.is_extinct() {
+            continue;
+        }
+        pest_pop.develop();
+        pest_pop.kill_adults(protections.trap_share());
+        // Until the species' colonies find the crops its feeders have
+        // nothing to breed from, unless there's nothing planted to find
+        if !hosts.is_empty() && pest_pop.has_feeders() && !pest_pop.fed_tonight() {
+            continue;
         }
+        pest_pop.forage();
+        pest_pop.next_generation(pest_pop.carrying_capacity(&hosts));
+        if pest_pop.is_extinct() {
+            println!("{}s have died out", pest_pop.get_species().get_name());
+        }
+    }
 
-        // fade to white because the sun is coming up
-        if bug_night_result > pest_pop.get_avg_attack_chance() {
-            let mut i = 0;
-            while i < 254 {
-                wincan
-                    .copy(
-                        &texture_creator
-                            .load_texture("src/images/sleeping_screen.png")
-                            .unwrap(),
-                        None,
-                        None,
-                    )
-                    .unwrap();
-                wincan.set_draw_color(Color::RGBA(255, 255, 255, i));
-                wincan.fill_rect(r).unwrap();
-                wincan.present();
-                // thread::sleep(Duration::from_millis(1));
-                thread::sleep(Duration::from_micros(250));
-                i = i + 2;
-            }
-        } else {
-            let mut i = 0;
-            while i < 254 {
-                wincan
-                    .copy(
-                        &texture_creator
-                            .load_texture("src/images/sleeping_screen_spiders.png")
-                            .unwrap(),
-                        None,
-                        None,
-                    )
-                    .unwrap();
-                wincan.set_draw_color(Color::RGBA(255, 255, 255, i));
-                wincan.fill_rect(r).unwrap();
-                wincan.present();
-                thread::sleep(Duration::from_millis(1));
-                i = i + 2;
+    // The valley trades overnight, which sets tomorrow's prices and
+    // stock at the market
+    economy.night();
+    store.restock(economy);
+
+    // Turn the calendar. Crops that can't grow in the new season die off.
+    if clock.start_new_day() {
+        let new_season = clock.get_calendar().get_season();
+        for _x in 0..pop.width() {
+            for _y in 0..pop.height() {
+                let _c = pop.get_crop_with_index_mut(_x, _y);
+                let t = _c.get_crop_type_enum();
+                if t != CropType::None && !_c.rotten() && !t.grows_in(new_season) {
+                    _c.set_rotten(true);
+                    _c.set_stage(0);
+                    _c.set_water(false);
+                    _c.set_genes(None);
+                }
             }
         }
+        println!("{} has begun", new_season);
+    }
+    weather.advance(clock.get_calendar().get_season());
 
-        in_menu = None;
-        return in_menu;
-    } else if keystate.contains(&Keycode::N) {
-        //Player has chosen not to sleep
-        in_menu = None;
-        return in_menu;
+    // Eat dinner.
+    let hunger = player.dinner();
+    if hunger > 0 {
+        println!("how sad, your are still {} hungery", hunger)
+    }
+
+    // fade to white because the sun is coming up
+    if !bug_night {
+        let mut i = 0;
+        while i < 254 {
+            wincan
+                .copy(
+                    &texture_creator
+                        .load_texture("src/images/sleeping_screen.png")
+                        .unwrap(),
+                    None,
+                    None,
+                )
+                .unwrap();
+            wincan.set_draw_color(Color::RGBA(255, 255, 255, i));
+            wincan.fill_rect(r).unwrap();
+            wincan.present();
+            // thread::sleep(Duration::from_millis(1));
+            thread::sleep(Duration::from_micros(250));
+            i = i + 2;
+        }
     } else {
-        return in_menu;
+        let mut i = 0;
+        while i < 254 {
+            wincan
+                .copy(
+                    &texture_creator
+                        .load_texture("src/images/sleeping_screen_spiders.png")
+                        .unwrap(),
+                    None,
+                    None,
+                )
+                .unwrap();
+            wincan.set_draw_color(Color::RGBA(255, 255, 255, i));
+            wincan.fill_rect(r).unwrap();
+            wincan.present();
+            thread::sleep(Duration::from_millis(1));
+            i = i + 2;
+        }
     }
 }
@@ -0,0 +1,193 @@
+//! Module for sprinklers: placeable items that water the tiles
+//! around them during the nightly step
+
+use sdl2::image::LoadTexture;
+use sdl2::rect::Rect;
+use sdl2::render::{Texture, TextureCreator};
+use sdl2::video::WindowContext;
+use std::str::FromStr;
+
+use crate::crop::CropType;
+use crate::genes;
+use crate::item::Item;
+use crate::population::Population;
+use crate::InventoryItemTrait;
+use crate::TILE_SIZE;
+
+/// Sprinkler upgrade tiers. Higher tiers water larger patterns.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum SprinklerTier {
+    Basic,
+    Quality,
+    Deluxe,
+}
+
+impl SprinklerTier {
+    /// Path of the texture used both on the map and in the inventory
+    pub fn tex_path(&self) -> &'static str {
+        match self {
+            SprinklerTier::Basic => "src/images/sprinkler_basic.png",
+            SprinklerTier::Quality => "src/images/sprinkler_quality.png",
+            SprinklerTier::Deluxe => "src/images/sprinkler_deluxe.png",
+        }
+    }
+
+    /// Map an item texture path back to a sprinkler tier.
+    /// Returns None if the item is not a sprinkler.
+    pub fn from_tex_path(path: &str) -> Option<SprinklerTier> {
+        match path {
+            "src/images/sprinkler_basic.png" => Some(SprinklerTier::Basic),
+            "src/images/sprinkler_quality.png" => Some(SprinklerTier::Quality),
+            "src/images/sprinkler_deluxe.png" => Some(SprinklerTier::Deluxe),
+            _ => None,
+        }
+    }
+
+    /// Tile offsets (relative to the sprinkler) watered each night
+    /// * Basic - the four adjacent tiles
+    /// * Quality - the eight surrounding tiles
+    /// * Deluxe - the 5x5 square around the sprinkler
+    pub fn pattern(&self) -> Vec<(i32, i32)> {
+        match self {
+            SprinklerTier::Basic => vec![(0, -1), (-1, 0), (1, 0), (0, 1)],
+            SprinklerTier::Quality => square_pattern(1),
+            SprinklerTier::Deluxe => square_pattern(2),
+        }
+    }
+
+    /// Value used to sort sprinklers in the inventory, so the best
+    /// sprinkler is placed first
+    fn rank(&self) -> i32 {
+        match self {
+            SprinklerTier::Basic => 1,
+            SprinklerTier::Quality => 2,
+            SprinklerTier::Deluxe => 3,
+        }
+    }
+}
+
+impl FromStr for SprinklerTier {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "Basic" => Ok(SprinklerTier::Basic),
+            "Quality" => Ok(SprinklerTier::Quality),
+            "Deluxe" => Ok(SprinklerTier::Deluxe),
+            _ => Err(()),
+        }
+    }
+}
+
+impl std::fmt::Display for SprinklerTier {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            SprinklerTier::Basic => write!(f, "Basic"),
+            SprinklerTier::Quality => write!(f, "Quality"),
+            SprinklerTier::Deluxe => write!(f, "Deluxe"),
+        }
+    }
+}
+
+/// Every tile in a square of the given radius, except the center
+fn square_pattern(radius: i32) -> Vec<(i32, i32)> {
+    let mut v = Vec::new();
+    for dx in -radius..=radius {
+        for dy in -radius..=radius {
+            if dx != 0 || dy != 0 {
+                v.push((dx, dy));
+            }
+        }
+    }
+    v
+}
+
+/// A sprinkler sitting in the player's inventory
+pub struct Sprinkler<'a> {
+    tier: SprinklerTier,
+    texture: Texture<'a>,
+}
+
+impl<'a> Sprinkler<'a> {
+    pub fn new(
+        tier: SprinklerTier,
+        texture_creator: &'a TextureCreator<WindowContext>,
+    ) -> Sprinkler<'a> {
+        Sprinkler {
+            tier,
+            texture: texture_creator.load_texture(tier.tex_path()).unwrap(),
+        }
+    }
+}
+
+impl InventoryItemTrait for Sprinkler<'_> {
+    fn get_value(&self) -> i32 {
+        self.tier.rank()
+    }
+    fn texture(&self) -> &Texture {
+        &self.texture
+    }
+    fn src(&self) -> Rect {
+        Rect::new(0, 0, TILE_SIZE, TILE_SIZE)
+    }
+    /// Placing a sprinkler needs the area's item list, so it is
+    /// handled in the game loop instead
+    fn inventory_input(
+        &self,
+        _square: (i32, i32),
+        _pop: &mut Population,
+    ) -> Option<(Option<CropType>, Option<genes::Genes>, Option<genes::Genes>)> {
+        None
+    }
+    fn to_save_string(&self) -> Option<String> {
+        Some(format!("sprinkler;{}\n", self.tier))
+    }
+    fn placed_tex_path(&self) -> Option<&'static str> {
+        Some(self.tier.tex_path())
+    }
+}
+
+/// Returns true if the tile at index (x, y) has nothing in the way of a
+/// new sprinkler, flower bed or protection
+pub fn can_place(square: (i32, i32), pop: &Population, item_vec: &[Item]) -> bool {
+    let (x, y) = square;
+    let tile_rect = Rect::new(
+        x * TILE_SIZE as i32,
+        y * TILE_SIZE as i32,
+        TILE_SIZE,
+        TILE_SIZE,
+    );
+    !pop.get_tile_with_index(x as u32, y as u32).tilled()
+        && pop
+            .get_tile_with_index(x as u32, y as u32)
+            .terrain()
+            .walkable()
+        && pop
+            .get_crop_with_index(x as u32, y as u32)
+            .get_crop_type_enum()
+            == CropType::None
+        && !item_vec.iter().any(|i| i.pos().has_intersection(tile_rect))
+}
+
+/// Nightly step: every sprinkler in `item_vec` waters the tilled tiles
+/// (and any crops on them) in its pattern
+pub fn water_from_sprinklers(item_vec: &[Item], pop: &mut Population) {
+    for item in item_vec {
+        if let Some(tier) = SprinklerTier::from_tex_path(item.tex_path()) {
+            let sx = item.x() / TILE_SIZE as i32;
+            let sy = item.y() / TILE_SIZE as i32;
+            for (dx, dy) in tier.pattern() {
+                let (x, y) = (sx + dx, sy + dy);
+                if !pop.in_bounds(x, y) {
+                    continue;
+                }
+                if pop.get_tile_with_index(x as u32, y as u32).tilled() {
+                    pop.get_tile_with_index_mut(x as u32, y as u32)
+                        .set_water(true);
+                    pop.get_crop_with_index_mut(x as u32, y as u32)
+                        .set_water(true);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,147 @@
+//! Module for the graph screen showing how each pest population has
+//! evolved, generation by generation
+
+use sdl2::pixels::Color;
+use sdl2::rect::{Point, Rect};
+use sdl2::render::{Texture, WindowCanvas};
+
+use crate::hud;
+use crate::pest;
+use crate::pest_population::PestPopulation;
+use crate::pest_stats::GENE_NAMES;
+
+/// Which population and gene the graph screen shows
+pub struct StatsView {
+    species: usize,
+    gene: usize,
+}
+
+impl StatsView {
+    pub fn new() -> StatsView {
+        StatsView {
+            species: 0,
+            gene: 0,
+        }
+    }
+
+    /// Move to the next or previous species
+    pub fn cycle_species(&mut self, dir: i32, num_species: usize) {
+        self.species = (self.species as i32 + dir).rem_euclid(num_species.max(1) as i32) as usize;
+    }
+
+    /// Move to the next or previous gene
+    pub fn cycle_gene(&mut self, dir: i32) {
+        self.gene = (self.gene as i32 + dir).rem_euclid(pest::NUM_GENES as i32) as usize;
+    }
+
+    /*
+       Draws the graph screen: the selected gene's range in each generation
+       with its mean on top, the adults' mean fitness and the population's
+       size, which is scaled to its largest size so far
+    */
+    pub fn draw(&self, wincan: &mut WindowCanvas, font: &Texture, pest_pops: &[PestPopulation]) {
+        let pest_pop = match pest_pops.get(self.species) {
+            Some(p) => p,
+            None => return,
+        };
+        let history = pest_pop.get_stats();
+
+        // Canvas and chart area
+        wincan.set_draw_color(Color::RGBA(30, 30, 40, 235));
+        wincan.fill_rect(Rect::new(140, 20, 1000, 660)).unwrap();
+        let chart = Rect::new(200, 120, 880, 460);
+        wincan.set_draw_color(Color::RGBA(60, 60, 75, 255));
+        wincan.fill_rect(chart).unwrap();
+
+        hud::draw_text(
+            wincan,
+            font,
+            &format!(
+                "{}: {} - {} generations",
+                pest_pop.get_species().get_name(),
+                GENE_NAMES[self.gene],
+                history.len()
+            ),
+            160,
+            40,
+            4,
+        );
+        hud::draw_text(
+            wincan,
+            font,
+            "Left/Right: species  Up/Down: gene  Q: close",
+            160,
+            80,
+            3,
+        );
+
+        if history.is_empty() {
+            hud::draw_text(wincan, font, "No generations yet", 220, 140, 4);
+            return;
+        }
+
+        let max_size = history.iter().map(|s| s.size).max().unwrap_or(1).max(1) as f32;
+        let x_at = |i: usize| {
+            chart.x()
+                + (i as f32 * (chart.width() - 1) as f32 / (history.len() - 1).max(1) as f32) as i32
+        };
+        // Every value drawn runs from 0 to 1
+        let y_at =
+            |v: f32| chart.bottom() - 1 - (v.clamp(0.0, 1.0) * (chart.height() - 1) as f32) as i32;
+
+        // Range of the gene in each generation
+        wincan.set_draw_color(Color::RGBA(120, 170, 230, 90));
+        for (i, s) in history.iter().enumerate() {
+            let g = s.genes[self.gene];
+            wincan
+                .draw_line(
+                    Point::new(x_at(i), y_at(g.min)),
+                    Point::new(x_at(i), y_at(g.max)),
+                )
+                .unwrap();
+        }
+
+        let series = [
+            (
+                Color::RGB(200, 200, 200),
+                history
+                    .iter()
+                    .map(|s| s.size as f32 / max_size)
+                    .collect::<Vec<f32>>(),
+            ),
+            (
+                Color::RGB(240, 190, 60),
+                history.iter().map(|s| s.fitness.mean).collect(),
+            ),
+            (
+                Color::RGB(120, 200, 255),
+                history.iter().map(|s| s.genes[self.gene].mean).collect(),
+            ),
+        ];
+        for (color, values) in series.iter() {
+            wincan.set_draw_color(*color);
+            let points: Vec<Point> = values
+                .iter()
+                .enumerate()
+                .map(|(i, v)| Point::new(x_at(i), y_at(*v)))
+                .collect();
+            wincan.draw_lines(&points[..]).unwrap();
+        }
+
+        let last = history[history.len() - 1];
+        let g = last.genes[self.gene];
+        hud::draw_panel(
+            wincan,
+            font,
+            &[
+                format!("Gene mean: {:.2}  variance: {:.3}", g.mean, g.variance),
+                format!("Gene range: {:.2} - {:.2}", g.min, g.max),
+                format!("Fitness mean: {:.2}", last.fitness.mean),
+                format!("Size: {} of max {}", last.size, max_size),
+            ],
+            200,
+            595,
+            3,
+        );
+    }
+}
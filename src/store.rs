@@ -4,7 +4,32 @@ use sdl2::rect::Rect;
 
 use sdl2::render::WindowCanvas;
 
-use crate::market_item::MarketItem;
+use crate::crop::CropType;
+use crate::economy::Economy;
+use crate::market_item::{Good, MarketItem};
+use crate::protection::ProtectionKind;
+use crate::sprinkler::SprinklerTier;
+
+/// Height of one row in the store's item list; 18 rows fill the list
+const ROW_HEIGHT: i32 = 32;
+
+/// What the player walks away with after a confirmed purchase
+pub enum Purchase {
+    /// amount, crop type and growth stage of seeds or crops
+    Crops(i32, CropType, i32),
+    /// amount and tier of sprinklers
+    Sprinklers(i32, SprinklerTier),
+    /// number of land plots
+    Land(i32),
+    /// number of cans of pesticide
+    Pesticide(i32),
+    /// number of flower beds
+    FlowerBeds(i32),
+    /// amount and kind of scarecrows, sticky traps or row covers
+    Protections(i32, ProtectionKind),
+    /// number of bottles of fungicide
+    Fungicide(i32),
+}
 
 pub struct Store<'a> {
     item_selected: i32,
@@ -65,7 +90,12 @@ impl<'a> Store<'a> {
         // selection
         wincan.set_draw_color(Color::RGBA(255, 0, 0, 60));
         wincan
-            .fill_rect(Rect::new(150, 30 + self.item_selected * 50, 500, 50))
+            .fill_rect(Rect::new(
+                150,
+                30 + self.item_selected * ROW_HEIGHT,
+                500,
+                ROW_HEIGHT as u32,
+            ))
             .unwrap();
 
         // submenu
@@ -94,7 +124,7 @@ impl<'a> Store<'a> {
             .unwrap();
 
         let item_textures = texture_creator
-            .load_texture("src/images/Crop_Tileset.png")
+            .load_texture(self.items_array[self.item_selected as usize].tex_path())
             .unwrap();
         wincan
             .copy(
@@ -209,50 +239,81 @@ impl<'a> Store<'a> {
                 .copy(
                     &market_menu_items,
                     Rect::new(0, item.item_label_offset, 100, 6),
-                    Rect::new(150, 30 + i * 50, 500, 50),
+                    Rect::new(150, 30 + i * ROW_HEIGHT, 500, ROW_HEIGHT as u32),
                 )
                 .unwrap();
-            Store::price_draw(wincan, 3, 320, 45 + i * 50, item.amount);
-            Store::price_draw(wincan, 3, 410, 45 + i * 50, item.min);
-            Store::price_draw(wincan, 3, 530, 45 + i * 50, item.max);
+            Store::price_draw(wincan, 3, 320, 33 + i * ROW_HEIGHT, item.amount);
+            Store::price_draw(wincan, 3, 410, 33 + i * ROW_HEIGHT, item.min);
+            Store::price_draw(wincan, 3, 530, 33 + i * ROW_HEIGHT, item.max);
             i = i + 1;
         }
     }
 
-    pub fn confirm_purchase(&mut self) -> Option<(i32, crate::crop::CropType, i32)> {
+    /// Buy the selected amount of the selected item if the price offered
+    /// covers it, the player has `money` to pay it and, for land, the farm
+    /// has `plots_left` to grow into. Returns what was bought and the price
+    /// offered, which the player pays.
+    pub fn confirm_purchase(&mut self, money: i32, plots_left: i32) -> Option<(Purchase, i32)> {
         let total = self.items_array[self.item_selected as usize].min * self.amount_selected;
 
         let mut t = None;
 
-        if total <= self.price && total != 0 {
-            t = Some((
-                self.amount_selected,
-                self.items_array[self.item_selected as usize].crop,
-                self.items_array[self.item_selected as usize].growth,
-            ));
-
-            // self.items_array[self.item_selected as usize].amount =
-            // self.items_array[self.item_selected as usize].amount - self.amount_selected;
+        if self.items_array[self.item_selected as usize].amount < self.amount_selected {
+            println!("That's sold out");
+        } else if self.price > money {
+            println!("You can't afford that");
+        } else if self.items_array[self.item_selected as usize].good == Good::Land
+            && self.amount_selected > plots_left
+        {
+            println!("The farm can't grow any bigger");
+        } else if total <= self.price && total != 0 {
+            let item = &self.items_array[self.item_selected as usize];
+            t = match item.good {
+                Good::Crop => Some(Purchase::Crops(
+                    self.amount_selected,
+                    item.crop,
+                    item.growth,
+                )),
+                Good::Sprinkler(tier) => Some(Purchase::Sprinklers(self.amount_selected, tier)),
+                Good::Land => Some(Purchase::Land(self.amount_selected)),
+                Good::Pesticide => Some(Purchase::Pesticide(self.amount_selected)),
+                Good::FlowerBed => Some(Purchase::FlowerBeds(self.amount_selected)),
+                Good::Protection(kind) => Some(Purchase::Protections(self.amount_selected, kind)),
+                Good::Fungicide => Some(Purchase::Fungicide(self.amount_selected)),
+            };
 
-            // loop for self.amount_selected
-            // add seed type to the inventory
-            // self.items_array[self.item_selected as usize].crop is the enum of the crop type
-
-            /*
-            let _c = Crop::new(
-                Rect::new(0, 0, 80, 80),
-                0,
-                texture,
-                false,
-                CropType::Lettuce,
-                Some(Genes::new()),
-            );
-            p.add_item(_c);
-            */
+            self.items_array[self.item_selected as usize].take(self.amount_selected);
         }
 
+        let cost = self.price;
         self.amount_selected = 1;
         self.price = 1;
-        t
+        t.map(|purchase| (purchase, cost))
+    }
+
+    /// Restock every item for the night and price it from the economy
+    pub fn restock(&mut self, economy: &Economy) {
+        for item in self.items_array.iter_mut() {
+            item.update(economy);
+            item.restock();
+        }
+    }
+
+    /// Save string of how many of each item are in stock, in the order
+    /// of the items
+    pub fn to_save_string(&self) -> String {
+        let mut s = String::from("stock");
+        for item in self.items_array.iter() {
+            s.push_str(&format!(";{}", item.amount));
+        }
+        s.push('\n');
+        s
+    }
+
+    /// Price every item from the economy without restocking
+    pub fn update_prices(&mut self, economy: &Economy) {
+        for item in self.items_array.iter_mut() {
+            item.update(economy);
+        }
     }
 }
@@ -0,0 +1,196 @@
+//! Module for terrain types and the tile-map files that lay
+//! them out on an area's grid
+
+use sdl2::rect::Rect;
+use std::fs::File;
+use std::io::Read;
+use std::str::FromStr;
+
+use crate::population::Population;
+use crate::TILE_SIZE;
+
+/// Terrain type enum
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum TerrainType {
+    Grass,
+    Dirt,
+    Path,
+    Water,
+    Rock,
+    Fence,
+}
+
+impl TerrainType {
+    /// Rectangle on `Background_Tileset.png` for this terrain
+    pub fn src(&self) -> Rect {
+        let (x, y) = match self {
+            TerrainType::Grass => (0, 0),
+            TerrainType::Rock => (1, 0),
+            TerrainType::Dirt => (0, 2),
+            TerrainType::Path => (1, 2),
+            TerrainType::Water => (2, 2),
+            TerrainType::Fence => (3, 2),
+        };
+        Rect::new(
+            x * TILE_SIZE as i32,
+            y * TILE_SIZE as i32,
+            TILE_SIZE,
+            TILE_SIZE,
+        )
+    }
+}
+
+impl FromStr for TerrainType {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "Grass" => Ok(TerrainType::Grass),
+            "Dirt" => Ok(TerrainType::Dirt),
+            "Path" => Ok(TerrainType::Path),
+            "Water" => Ok(TerrainType::Water),
+            "Rock" => Ok(TerrainType::Rock),
+            "Fence" => Ok(TerrainType::Fence),
+            _ => Err(()),
+        }
+    }
+}
+
+/// A terrain type along with the rules the map file gave it
+#[derive(Copy, Clone, Debug)]
+pub struct Terrain {
+    t: TerrainType,
+    /// Can the hoe till this tile
+    tillable: bool,
+    /// Can the player walk over this tile
+    walkable: bool,
+    /// Does this tile water the tilled tiles next to it each night
+    water_source: bool,
+}
+
+impl Terrain {
+    pub fn new(t: TerrainType, tillable: bool, walkable: bool, water_source: bool) -> Terrain {
+        Terrain {
+            t,
+            tillable,
+            walkable,
+            water_source,
+        }
+    }
+
+    /// Plain grass; used for any tile the map file doesn't cover
+    pub fn grass() -> Terrain {
+        Terrain::new(TerrainType::Grass, true, true, false)
+    }
+
+    pub fn get_type(&self) -> TerrainType {
+        self.t
+    }
+
+    pub fn tillable(&self) -> bool {
+        self.tillable
+    }
+
+    pub fn walkable(&self) -> bool {
+        self.walkable
+    }
+
+    pub fn water_source(&self) -> bool {
+        self.water_source
+    }
+
+    pub fn src(&self) -> Rect {
+        self.t.src()
+    }
+}
+
+/// Terrain for every tile of an area, as laid out in its tile map file.
+/// The map may be larger than the area, so land bought later already
+/// has its terrain designed.
+pub struct TileMap {
+    rows: Vec<Vec<Terrain>>,
+}
+
+impl TileMap {
+    /// Load a tile map file.
+    /// File format, one entry per line:
+    /// * `terrain;<symbol>;<type>;<tillable>;<walkable>;<water source>`
+    ///   defines the rules for a map symbol
+    /// * `row;<symbols>` is one row of tiles, top to bottom
+    /// * lines starting with `#` are comments
+    pub fn load(path: &str) -> TileMap {
+        let mut map_file = File::open(path).expect("Can't open tile map file");
+        let mut contents = String::new();
+        map_file
+            .read_to_string(&mut contents)
+            .expect("Can't read tile map file");
+
+        let mut legend: Vec<(char, Terrain)> = Vec::new();
+        let mut rows = Vec::new();
+        for line in contents.lines() {
+            let results: Vec<&str> = line.split(";").collect();
+            if results[0] == "terrain" {
+                legend.push((
+                    results[1].chars().next().unwrap(),
+                    Terrain::new(
+                        results[2].parse::<TerrainType>().unwrap(),
+                        results[3].parse::<bool>().unwrap(),
+                        results[4].parse::<bool>().unwrap(),
+                        results[5].parse::<bool>().unwrap(),
+                    ),
+                ));
+            } else if results[0] == "row" {
+                let mut row = Vec::new();
+                for symbol in results[1].chars() {
+                    if let Some((_, t)) = legend.iter().find(|(s, _)| *s == symbol) {
+                        row.push(*t);
+                    } else {
+                        println!("Unknown terrain symbol {} in {}", symbol, path);
+                        row.push(Terrain::grass());
+                    }
+                }
+                rows.push(row);
+            }
+        }
+        TileMap { rows }
+    }
+
+    /// Terrain at index (x, y); grass if the map file doesn't reach it
+    pub fn get(&self, x: usize, y: usize) -> Terrain {
+        self.rows
+            .get(y)
+            .and_then(|row| row.get(x))
+            .copied()
+            .unwrap_or_else(Terrain::grass)
+    }
+}
+
+/// Nightly step: water source tiles keep the tilled tiles
+/// directly next to them watered
+pub fn irrigate(pop: &mut Population) {
+    let mut to_water = Vec::new();
+    for (x, col) in pop.get_vec().iter().enumerate() {
+        for (y, crop_tile) in col.iter().enumerate() {
+            if !crop_tile.tile.terrain().water_source() {
+                continue;
+            }
+            for (dx, dy) in [(0, -1), (-1, 0), (1, 0), (0, 1)] {
+                let nx = x as i32 + dx;
+                let ny = y as i32 + dy;
+                if nx >= 0
+                    && ny >= 0
+                    && (nx as usize) < pop.get_vec().len()
+                    && (ny as usize) < col.len()
+                {
+                    to_water.push((nx as u32, ny as u32));
+                }
+            }
+        }
+    }
+    for (x, y) in to_water {
+        if pop.get_tile_with_index(x, y).tilled() {
+            pop.get_tile_with_index_mut(x, y).set_water(true);
+            pop.get_crop_with_index_mut(x, y).set_water(true);
+        }
+    }
+}
@@ -1,6 +1,7 @@
 use sdl2::rect::Rect;
 use sdl2::render::Texture;
 
+use crate::terrain::Terrain;
 use crate::TILE_SIZE;
 
 pub struct Tile<'a> {
@@ -8,16 +9,18 @@ pub struct Tile<'a> {
     src: Rect,
     texture: &'a Texture<'a>,
     tilled: bool,
+    terrain: Terrain,
 }
 
 impl<'a> Tile<'a> {
-    pub fn new(pos: Rect, texture: &'a Texture<'a>) -> Tile {
-        let src = Rect::new(0, 0, TILE_SIZE, TILE_SIZE);
+    pub fn new(pos: Rect, texture: &'a Texture<'a>, terrain: Terrain) -> Tile {
+        let src = terrain.src();
         Tile {
             pos,
             src,
             texture,
             tilled: false,
+            terrain,
         }
     }
 
@@ -53,12 +56,16 @@ impl<'a> Tile<'a> {
         self.tilled
     }
 
+    pub fn terrain(&self) -> Terrain {
+        self.terrain
+    }
+
     pub fn set_tilled(&mut self, till: bool) {
         self.tilled = till;
         if till {
             self.src = Rect::new(TILE_SIZE as i32, TILE_SIZE as i32, TILE_SIZE, TILE_SIZE);
         } else {
-            self.src = Rect::new(0, 0, TILE_SIZE, TILE_SIZE);
+            self.src = self.terrain.src();
         }
     }
 
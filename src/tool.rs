@@ -80,7 +80,8 @@ impl InventoryItemTrait for Tool<'_> {
                         println!("None");
                     }
                 }
-                // If crop rotten, don't harvest, just remove
+                // If crop rotten, don't harvest, just remove, along with
+                // any eggs laid in its soil
                 if pop.get_crop_with_index(x as u32, y as u32).rotten() {
                     let mut _c = pop.get_crop_with_index_mut(x as u32, y as u32);
                     _c.set_stage(0);
@@ -89,17 +90,37 @@ impl InventoryItemTrait for Tool<'_> {
                     _c.set_water(false);
                     _c.set_genes(None);
                     _c.set_child(None);
+                    pop.set_colony(x as u32, y as u32, None);
+                    pop.set_disease(x as u32, y as u32, None);
                     return None;
                 }
 
-                // If tile has plant ready to harvest, harvest
+                // Pull up infested or diseased plants that aren't ready
+                // yet, pests, disease and all
+                if (pop.get_colony(x as u32, y as u32).is_some()
+                    || pop.get_disease(x as u32, y as u32).is_some())
+                    && pop.get_crop_with_index(x as u32, y as u32).get_stage() != 3
+                {
+                    let mut _c = pop.get_crop_with_index_mut(x as u32, y as u32);
+                    _c.set_stage(0);
+                    _c.set_crop_type_enum(CropType::None);
+                    _c.set_water(false);
+                    _c.set_genes(None);
+                    _c.set_child(None);
+                    pop.set_colony(x as u32, y as u32, None);
+                    pop.set_disease(x as u32, y as u32, None);
+                    return None;
+                }
+
+                // If tile has plant ready to harvest, harvest. Damage
+                // from pests lowers the harvest's value.
                 if pop.get_crop_with_index(x as u32, y as u32).get_stage() == 3 {
-                    let _g = pop
-                        .get_crop_with_index(x as u32, y as u32)
+                    let _c = pop.get_crop_with_index(x as u32, y as u32);
+                    let _g = _c
                         .get_all_genes()
                         .as_ref()
                         .unwrap()
-                        .clone();
+                        .with_quality(_c.get_health());
                     let mut _c = pop.get_crop_with_index_mut(x as u32, y as u32);
                     let return_crop_type = _c.get_crop_type_enum();
                     // let _g = _c.get_all_genes().unwrap().clone();
@@ -113,22 +134,30 @@ impl InventoryItemTrait for Tool<'_> {
                     // _t.set_tilled(false);
 
                     let child = _c.get_child().clone();
+                    pop.set_colony(x as u32, y as u32, None);
+                    pop.set_disease(x as u32, y as u32, None);
 
                     return Some((Some(return_crop_type), Some(_g), child));
                 }
             }
             // Hoe
             ToolType::Hoe => {
-                // If tile is empty, set as tilled dirt
+                // If tile is empty and its terrain allows it, set as tilled dirt
                 if pop
                     .get_crop_with_index(x as u32, y as u32)
                     .get_crop_type()
                     .to_owned()
                     == "None"
                     && !pop.get_tile_with_index(x as u32, y as u32).tilled()
+                    && pop
+                        .get_tile_with_index(x as u32, y as u32)
+                        .terrain()
+                        .tillable()
                 {
                     let mut _tile = pop.get_tile_with_index_mut(x as u32, y as u32);
                     _tile.set_tilled(true);
+                    // Turning the soil destroys any eggs in it
+                    pop.set_colony(x as u32, y as u32, None);
                 }
             }
             // Watering can
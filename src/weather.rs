@@ -0,0 +1,182 @@
+//! Module for the daily weather. Tomorrow's weather is rolled each
+//! night from a distribution that depends on the season, so the
+//! player can see it coming on the sleep screen.
+
+use rand::Rng;
+use std::fmt;
+use std::str::FromStr;
+
+use crate::calendar::Season;
+use crate::crop::{Crop, CropType};
+use crate::population::Population;
+
+/// How much faster water evaporates during a drought
+pub const DROUGHT_EVAPORATION: f32 = 2.0;
+
+/// Weather enum
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum Weather {
+    Sunny,
+    Rain,
+    Drought,
+    Frost,
+}
+
+impl Weather {
+    /// Chance of each kind of weather in `season`. Chances add up to 1.
+    fn chances(season: Season) -> [(Weather, f32); 4] {
+        match season {
+            Season::Spring => [
+                (Weather::Sunny, 0.55),
+                (Weather::Rain, 0.35),
+                (Weather::Drought, 0.05),
+                (Weather::Frost, 0.05),
+            ],
+            Season::Summer => [
+                (Weather::Sunny, 0.55),
+                (Weather::Rain, 0.2),
+                (Weather::Drought, 0.25),
+                (Weather::Frost, 0.0),
+            ],
+            Season::Fall => [
+                (Weather::Sunny, 0.55),
+                (Weather::Rain, 0.3),
+                (Weather::Drought, 0.05),
+                (Weather::Frost, 0.1),
+            ],
+            Season::Winter => [
+                (Weather::Sunny, 0.45),
+                (Weather::Rain, 0.15),
+                (Weather::Drought, 0.0),
+                (Weather::Frost, 0.4),
+            ],
+        }
+    }
+
+    /// Pick a random day's weather for `season`
+    pub fn roll(season: Season) -> Weather {
+        let mut roll: f32 = rand::thread_rng().gen();
+        for (w, chance) in Weather::chances(season) {
+            if roll < chance {
+                return w;
+            }
+            roll -= chance;
+        }
+        Weather::Sunny
+    }
+
+    /// Multiplier on how quickly crops lose their water
+    pub fn evaporation(&self) -> f32 {
+        match self {
+            Weather::Drought => DROUGHT_EVAPORATION,
+            _ => 1.0,
+        }
+    }
+}
+
+impl fmt::Display for Weather {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Weather::Sunny => write!(f, "Sunny"),
+            Weather::Rain => write!(f, "Rain"),
+            Weather::Drought => write!(f, "Drought"),
+            Weather::Frost => write!(f, "Frost"),
+        }
+    }
+}
+
+impl FromStr for Weather {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "Sunny" => Ok(Weather::Sunny),
+            "Rain" => Ok(Weather::Rain),
+            "Drought" => Ok(Weather::Drought),
+            "Frost" => Ok(Weather::Frost),
+            _ => Err(format!("invalid Weather: {}", s)),
+        }
+    }
+}
+
+/// Today's weather and the forecast for tomorrow
+pub struct WeatherState {
+    today: Weather,
+    tomorrow: Weather,
+}
+
+impl WeatherState {
+    pub fn new(season: Season) -> WeatherState {
+        WeatherState {
+            today: Weather::roll(season),
+            tomorrow: Weather::roll(season),
+        }
+    }
+
+    pub fn get_today(&self) -> Weather {
+        self.today
+    }
+
+    pub fn get_tomorrow(&self) -> Weather {
+        self.tomorrow
+    }
+
+    /// The forecast comes true and a new one is rolled for the day after,
+    /// in `season`
+    pub fn advance(&mut self, season: Season) {
+        self.today = self.tomorrow;
+        self.tomorrow = Weather::roll(season);
+    }
+
+    pub fn to_save_string(&self) -> String {
+        format!("weather;{};{}\n", self.today, self.tomorrow)
+    }
+
+    /// Parse a `weather;<today>;<tomorrow>` line, already split on `;`
+    pub fn from_save_string(s: &[&str]) -> WeatherState {
+        WeatherState {
+            today: s[1].parse::<Weather>().unwrap(),
+            tomorrow: s[2].parse::<Weather>().unwrap(),
+        }
+    }
+}
+
+/// Rain waters every tilled tile on the farm
+pub fn rain(pop: &mut Population) {
+    for x in 0..pop.width() {
+        for y in 0..pop.height() {
+            if pop.get_tile_with_index(x, y).tilled() {
+                pop.get_tile_with_index_mut(x, y).set_water(true);
+                pop.get_crop_with_index_mut(x, y).set_water(true);
+            }
+        }
+    }
+}
+
+/// Frost knocks a crop back a stage unless it's tolerant enough
+/// to shrug it off. Seedlings that get frostbitten die.
+pub fn frost(pop: &mut Population) {
+    let mut rng = rand::thread_rng();
+    for x in 0..pop.width() {
+        for y in 0..pop.height() {
+            let c = pop.get_crop_with_index_mut(x, y);
+            if c.get_crop_type_enum() == CropType::None || c.rotten() {
+                continue;
+            }
+            let roll: f32 = rng.gen();
+            if roll >= c.get_crop_type_enum().frost_tolerance() {
+                frostbite(c);
+            }
+        }
+    }
+}
+
+fn frostbite(c: &mut Crop) {
+    if c.get_stage() == 0 {
+        c.set_rotten(true);
+        c.set_water(false);
+        c.set_genes(None);
+    } else {
+        c.set_stage(c.get_stage() - 1);
+    }
+}